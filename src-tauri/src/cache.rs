@@ -21,6 +21,51 @@ pub struct ModVersion {
   pub published_date: String,
   pub assets: HashMap<String, Option<String>>,
   pub supported_games: Option<Vec<String>>, // TODO map to SupportedMap
+  #[serde(default)]
+  pub dependencies: Vec<ModDependency>,
+  #[serde(default)]
+  pub settings: ModVersionSettings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ModVersionSettings {
+  // JSON merged into the decompiler's config when installing the mod, empty when there is none
+  #[serde(default)]
+  pub decomp_config_override: String,
+  // play with the vanilla game's saves instead of the mod's own, its settings stay separate
+  #[serde(default)]
+  pub share_vanilla_saves: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ModDependency {
+  pub id: String,
+  // the source's name, defaults to the source the depending mod comes from
+  pub source: Option<String>,
+  // a semver requirement such as ">=1.2.0", any version will do when omitted
+  pub version: Option<String>,
+}
+
+impl ModVersion {
+  // `platform` is one of "windows", "linux" or "macos"
+  pub fn asset_url(&self, platform: &str) -> Option<&String> {
+    self.assets.get(platform).and_then(|url| url.as_ref())
+  }
+
+  pub fn supports_game(&self, game_name: &str) -> bool {
+    self
+      .supported_games
+      .as_ref()
+      .is_some_and(|games| games.iter().any(|game| game == game_name))
+  }
+
+  pub fn published(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(&self.published_date).ok()
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, TS)]
@@ -60,6 +105,19 @@ pub struct ModSourceData {
   pub texture_packs: HashMap<String, ModInfo>,
 }
 
+impl ModInfo {
+  // Versions that can be installed for the game on this platform, newest first
+  pub fn compatible_versions(&self, game_name: &str, platform: &str) -> Vec<&ModVersion> {
+    let mut versions: Vec<&ModVersion> = self
+      .versions
+      .iter()
+      .filter(|version| version.supports_game(game_name) && version.asset_url(platform).is_some())
+      .collect();
+    versions.sort_by_key(|version| std::cmp::Reverse(version.published()));
+    versions
+  }
+}
+
 pub struct LauncherCache {
   pub mod_sources: HashMap<String, ModSourceData>,
 }
@@ -71,6 +129,29 @@ impl LauncherCache {
     }
   }
 
+  // Sources are cached by their URL, mods refer to them by name
+  pub fn find_mod(&self, source_name: &str, mod_name: &str) -> Option<&ModInfo> {
+    self
+      .mod_sources
+      .values()
+      .find(|source| source.source_name == source_name)
+      .and_then(|source| source.mods.get(mod_name))
+  }
+
+  pub fn find_mod_version(
+    &self,
+    source_name: &str,
+    mod_name: &str,
+    version_name: &str,
+  ) -> Option<&ModVersion> {
+    self.find_mod(source_name, mod_name).and_then(|mod_info| {
+      mod_info
+        .versions
+        .iter()
+        .find(|version| version.version == version_name)
+    })
+  }
+
   pub async fn refresh_mod_sources(&mut self, sources: Vec<String>) -> Result<(), CacheError> {
     self.mod_sources.clear();
     for source in sources {
@@ -0,0 +1,597 @@
+// Headless command line mode, used when the launcher is started with a subcommand
+//
+// The subcommands go through the same config and command logic as the frontend, but no window
+// is ever created.  Every invocation prints a single JSON object to stdout and exits with
+// `EXIT_SUCCESS`, `EXIT_FAILURE` or `EXIT_USAGE`, logs go to stderr instead so the output stays
+// machine readable.
+
+use std::{collections::HashMap, time::Duration};
+
+use serde_json::{json, Value};
+use tauri::Manager;
+
+use crate::{
+  cache::LauncherCache,
+  commands::{
+    binaries::{
+      extract_and_validate_iso, launch_game, run_compiler, run_decompiler, InstallStepOutput,
+    },
+    cache::refresh_mod_sources,
+    config::{set_install_directory, update_setting_value},
+    features::mods::{generate_mod_source, install_plan, launch_mod, resolve_mod_install_plan},
+    support::generate_support_package,
+    versions::{download_version, list_downloaded_versions},
+    CommandError,
+  },
+  config::LauncherConfig,
+  process_registry::{ProcessRegistry, RunningGame},
+  session_history::{read_sessions, session_log_path},
+};
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "usage: opengoal-launcher <command> [--install-dir <dir>]
+
+commands:
+  install <game> --iso <path>
+  launch <game> [--profile <name>] [--wait]
+  versions list
+  versions download <version> [--url <url>]
+  versions activate <version>
+  mods install <game> <source> <mod> [--version <version>] [--iso <path>]
+  mods launch <game> <source> <mod> [--profile <name>] [--wait]
+  mods generate-source <dir> --name <source-name> --url <base-url> [--output <path>]
+  support-package <output-path> [--crash-report <id>]
+  help";
+
+// Options that take a value, and flags that don't
+const VALUE_OPTIONS: [&str; 8] = [
+  "--install-dir",
+  "--iso",
+  "--url",
+  "--version",
+  "--profile",
+  "--crash-report",
+  "--name",
+  "--output",
+];
+const FLAG_OPTIONS: [&str; 1] = ["--wait"];
+const SUBCOMMANDS: [&str; 6] = [
+  "install",
+  "launch",
+  "versions",
+  "mods",
+  "support-package",
+  "help",
+];
+
+pub enum CliCommand {
+  Install {
+    game_name: String,
+    iso_path: String,
+  },
+  Launch {
+    game_name: String,
+    launch_profile: Option<String>,
+    wait: bool,
+  },
+  VersionsList,
+  VersionsDownload {
+    version: String,
+    url: Option<String>,
+  },
+  VersionsActivate {
+    version: String,
+  },
+  ModsInstall {
+    game_name: String,
+    source_name: String,
+    mod_name: String,
+    version: Option<String>,
+    iso_path: Option<String>,
+  },
+  ModsLaunch {
+    game_name: String,
+    source_name: String,
+    mod_name: String,
+    launch_profile: Option<String>,
+    wait: bool,
+  },
+  ModsGenerateSource {
+    source_dir: String,
+    source_name: String,
+    base_url: String,
+    output_path: Option<String>,
+  },
+  SupportPackage {
+    output_path: String,
+    crash_report_id: Option<String>,
+  },
+  Help,
+}
+
+pub struct CliInvocation {
+  pub command: CliCommand,
+  pub install_dir: Option<String>,
+}
+
+struct ParsedArgs {
+  positional: Vec<String>,
+  options: HashMap<String, String>,
+  flags: Vec<String>,
+}
+
+impl ParsedArgs {
+  fn parse(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs {
+      positional: Vec::new(),
+      options: HashMap::new(),
+      flags: Vec::new(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+      if VALUE_OPTIONS.contains(&arg.as_str()) {
+        let value = args
+          .next()
+          .ok_or_else(|| format!("{arg} requires a value"))?;
+        parsed.options.insert(arg.clone(), value.clone());
+      } else if FLAG_OPTIONS.contains(&arg.as_str()) {
+        parsed.flags.push(arg.clone());
+      } else if arg.starts_with("--") {
+        return Err(format!("Unknown option '{arg}'"));
+      } else {
+        parsed.positional.push(arg.clone());
+      }
+    }
+    Ok(parsed)
+  }
+
+  // `count` positional arguments are expected after the subcommand words
+  fn expect_positional(&self, count: usize, command: &str) -> Result<&[String], String> {
+    if self.positional.len() != count {
+      return Err(format!(
+        "'{command}' expects {count} argument(s) but got {}",
+        self.positional.len()
+      ));
+    }
+    Ok(&self.positional)
+  }
+
+  fn option(&self, name: &str) -> Option<String> {
+    self.options.get(name).cloned()
+  }
+
+  fn required_option(&self, name: &str, command: &str) -> Result<String, String> {
+    self
+      .option(name)
+      .ok_or_else(|| format!("'{command}' requires {name}"))
+  }
+
+  fn flag(&self, name: &str) -> bool {
+    self.flags.iter().any(|flag| flag == name)
+  }
+}
+
+// Returns `None` when the arguments aren't a subcommand, in which case the GUI starts as usual
+pub fn parse_args(args: &[String]) -> Option<Result<CliInvocation, String>> {
+  let subcommand = args.first()?;
+  if !SUBCOMMANDS.contains(&subcommand.as_str()) {
+    return None;
+  }
+  Some(parse_subcommand(subcommand, &args[1..]))
+}
+
+fn parse_subcommand(subcommand: &str, args: &[String]) -> Result<CliInvocation, String> {
+  let mut parsed = ParsedArgs::parse(args)?;
+  let command = match subcommand {
+    "install" => {
+      let positional = parsed.expect_positional(1, "install")?;
+      CliCommand::Install {
+        game_name: positional[0].clone(),
+        iso_path: parsed.required_option("--iso", "install")?,
+      }
+    }
+    "launch" => {
+      let positional = parsed.expect_positional(1, "launch")?;
+      CliCommand::Launch {
+        game_name: positional[0].clone(),
+        launch_profile: parsed.option("--profile"),
+        wait: parsed.flag("--wait"),
+      }
+    }
+    "versions" => {
+      if parsed.positional.is_empty() {
+        return Err("'versions' expects one of list, download or activate".to_owned());
+      }
+      let action = parsed.positional.remove(0);
+      match action.as_str() {
+        "list" => {
+          parsed.expect_positional(0, "versions list")?;
+          CliCommand::VersionsList
+        }
+        "download" => CliCommand::VersionsDownload {
+          version: parsed.expect_positional(1, "versions download")?[0].clone(),
+          url: parsed.option("--url"),
+        },
+        "activate" => CliCommand::VersionsActivate {
+          version: parsed.expect_positional(1, "versions activate")?[0].clone(),
+        },
+        _ => return Err(format!("Unknown versions command '{action}'")),
+      }
+    }
+    "mods" => {
+      if parsed.positional.is_empty() {
+        return Err("'mods' expects one of install, launch or generate-source".to_owned());
+      }
+      let action = parsed.positional.remove(0);
+      match action.as_str() {
+        "install" => {
+          let positional = parsed.expect_positional(3, "mods install")?;
+          CliCommand::ModsInstall {
+            game_name: positional[0].clone(),
+            source_name: positional[1].clone(),
+            mod_name: positional[2].clone(),
+            version: parsed.option("--version"),
+            iso_path: parsed.option("--iso"),
+          }
+        }
+        "launch" => {
+          let positional = parsed.expect_positional(3, "mods launch")?;
+          CliCommand::ModsLaunch {
+            game_name: positional[0].clone(),
+            source_name: positional[1].clone(),
+            mod_name: positional[2].clone(),
+            launch_profile: parsed.option("--profile"),
+            wait: parsed.flag("--wait"),
+          }
+        }
+        "generate-source" => CliCommand::ModsGenerateSource {
+          source_dir: parsed.expect_positional(1, "mods generate-source")?[0].clone(),
+          source_name: parsed.required_option("--name", "mods generate-source")?,
+          base_url: parsed.required_option("--url", "mods generate-source")?,
+          output_path: parsed.option("--output"),
+        },
+        _ => return Err(format!("Unknown mods command '{action}'")),
+      }
+    }
+    "support-package" => {
+      let positional = parsed.expect_positional(1, "support-package")?;
+      CliCommand::SupportPackage {
+        output_path: positional[0].clone(),
+        crash_report_id: parsed.option("--crash-report"),
+      }
+    }
+    _ => CliCommand::Help,
+  };
+  Ok(CliInvocation {
+    command,
+    install_dir: parsed.option("--install-dir"),
+  })
+}
+
+fn print_json(value: Value) {
+  println!("{value}");
+}
+
+pub fn print_usage_error(error: &str) {
+  print_json(json!({ "success": false, "error": error, "usage": USAGE }));
+}
+
+fn check_step(output: InstallStepOutput) -> Result<(), CommandError> {
+  if output.success {
+    return Ok(());
+  }
+  Err(CommandError::Installation(
+    output
+      .msg
+      .unwrap_or_else(|| "Installation step failed".to_owned()),
+  ))
+}
+
+// Matches the platform's asset on the release, the same way the frontend picks one
+async fn official_release_download_url(version: &str) -> Result<String, CommandError> {
+  let release: Value = reqwest::Client::new()
+    .get(format!(
+      "https://api.github.com/repos/open-goal/jak-project/releases/tags/{version}"
+    ))
+    .header("User-Agent", "opengoal-launcher")
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+  let platform = match std::env::consts::OS {
+    "macos" => "macos",
+    "windows" => "windows",
+    _ => "linux",
+  };
+  let arch = if std::env::consts::ARCH == "aarch64" {
+    "arm"
+  } else {
+    "intel"
+  };
+  release
+    .get("assets")
+    .and_then(|assets| assets.as_array())
+    .into_iter()
+    .flatten()
+    .filter_map(|asset| {
+      let name = asset.get("name")?.as_str()?.to_lowercase();
+      let url = asset.get("browser_download_url")?.as_str()?;
+      Some((name, url.to_owned()))
+    })
+    .find(|(name, _)| {
+      name.contains(platform)
+        && !name.contains(".bin")
+        && !name.contains("lsp")
+        && (platform != "macos" || name.contains(arch))
+    })
+    .map(|(_, url)| url)
+    .ok_or_else(|| {
+      CommandError::VersionManagement(format!(
+        "Unable to find a {platform} download for version {version}"
+      ))
+    })
+}
+
+async fn launched_game(
+  app_handle: &tauri::AppHandle,
+  game_name: &str,
+  mod_info: Option<(&str, &str)>,
+) -> Option<RunningGame> {
+  app_handle
+    .state::<tokio::sync::Mutex<ProcessRegistry>>()
+    .lock()
+    .await
+    .find(game_name, mod_info)
+    .cloned()
+}
+
+// Describes the launched game, waiting for it to exit first if asked to
+async fn launch_result(
+  app_handle: &tauri::AppHandle,
+  game: Option<RunningGame>,
+  wait: bool,
+) -> Result<Value, CommandError> {
+  let game = match game {
+    Some(game) => game,
+    None => return Ok(json!({ "pid": null })),
+  };
+  if !wait {
+    return Ok(json!({ "pid": game.pid }));
+  }
+  let registry = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+  while registry
+    .lock()
+    .await
+    .list()
+    .iter()
+    .any(|running| running.pid == game.pid)
+  {
+    tokio::time::sleep(Duration::from_millis(500)).await;
+  }
+  let session = session_log_path(app_handle)
+    .and_then(|log_path| read_sessions(&log_path).ok())
+    .and_then(|sessions| {
+      sessions
+        .into_iter()
+        .rev()
+        .find(|session| session.start_time == game.start_time)
+    });
+  Ok(json!({
+    "pid": game.pid,
+    "exitCode": session.as_ref().and_then(|session| session.exit_code),
+    "durationSeconds": session.as_ref().map(|session| session.duration_seconds),
+    "crashed": session.as_ref().map(|session| session.crashed),
+  }))
+}
+
+async fn run_command(
+  app_handle: &tauri::AppHandle,
+  command: CliCommand,
+) -> Result<Value, CommandError> {
+  let config = || app_handle.state::<tokio::sync::Mutex<LauncherConfig>>();
+  match command {
+    CliCommand::Install {
+      game_name,
+      iso_path,
+    } => {
+      check_step(
+        extract_and_validate_iso(
+          config(),
+          app_handle.clone(),
+          iso_path.clone(),
+          game_name.clone(),
+        )
+        .await?,
+      )?;
+      check_step(
+        run_decompiler(
+          config(),
+          app_handle.clone(),
+          iso_path.clone(),
+          game_name.clone(),
+          false,
+          false,
+        )
+        .await?,
+      )?;
+      check_step(
+        run_compiler(
+          config(),
+          app_handle.clone(),
+          iso_path,
+          game_name.clone(),
+          false,
+        )
+        .await?,
+      )?;
+      update_setting_value(
+        config(),
+        "installed".to_owned(),
+        json!(true),
+        Some(game_name.clone()),
+      )
+      .await?;
+      Ok(json!({ "game": game_name, "installed": true }))
+    }
+    CliCommand::Launch {
+      game_name,
+      launch_profile,
+      wait,
+    } => {
+      launch_game(
+        config(),
+        app_handle.clone(),
+        game_name.clone(),
+        false,
+        None,
+        launch_profile,
+        None,
+      )
+      .await?;
+      let game = launched_game(app_handle, &game_name, None).await;
+      launch_result(app_handle, game, wait).await
+    }
+    CliCommand::VersionsList => {
+      let downloaded = list_downloaded_versions(config(), "official".to_owned()).await?;
+      let active = config().lock().await.active_version.clone();
+      Ok(json!({ "active": active, "downloaded": downloaded }))
+    }
+    CliCommand::VersionsDownload { version, url } => {
+      let url = match url {
+        Some(url) => url,
+        None => official_release_download_url(&version).await?,
+      };
+      download_version(
+        config(),
+        app_handle.clone(),
+        version.clone(),
+        "official".to_owned(),
+        url.clone(),
+      )
+      .await?;
+      Ok(json!({ "version": version, "url": url }))
+    }
+    CliCommand::VersionsActivate { version } => {
+      let downloaded = list_downloaded_versions(config(), "official".to_owned()).await?;
+      if !downloaded.contains(&version) {
+        return Err(CommandError::VersionManagement(format!(
+          "Version {version} has not been downloaded"
+        )));
+      }
+      update_setting_value(config(), "active_version".to_owned(), json!(version), None).await?;
+      Ok(json!({ "active": version }))
+    }
+    CliCommand::ModsInstall {
+      game_name,
+      source_name,
+      mod_name,
+      version,
+      iso_path,
+    } => {
+      // installs the same way the launcher does, dependencies first and rolling back on failure, which
+      // needs the mod sources to plan from
+      let cache = app_handle.state::<tokio::sync::Mutex<LauncherCache>>();
+      refresh_mod_sources(cache.clone(), config()).await?;
+      let plan = resolve_mod_install_plan(
+        cache.clone(),
+        config(),
+        game_name.clone(),
+        mod_name.clone(),
+        source_name.clone(),
+        version,
+      )
+      .await?;
+      check_step(install_plan(&cache, &config(), app_handle, &game_name, &plan, &iso_path).await?)?;
+      let installed: Vec<Value> = plan
+        .iter()
+        .map(|planned| {
+          json!({
+            "source": planned.source_name,
+            "mod": planned.mod_name,
+            "version": planned.version,
+          })
+        })
+        .collect();
+      Ok(json!({
+        "game": game_name,
+        "source": source_name,
+        "mod": mod_name,
+        "version": plan.last().map(|planned| planned.version.clone()),
+        "installed": installed,
+      }))
+    }
+    CliCommand::ModsLaunch {
+      game_name,
+      source_name,
+      mod_name,
+      launch_profile,
+      wait,
+    } => {
+      launch_mod(
+        config(),
+        app_handle.clone(),
+        game_name.clone(),
+        false,
+        mod_name.clone(),
+        source_name.clone(),
+        launch_profile,
+        None,
+      )
+      .await?;
+      let game = launched_game(app_handle, &game_name, Some((&source_name, &mod_name))).await;
+      launch_result(app_handle, game, wait).await
+    }
+    CliCommand::ModsGenerateSource {
+      source_dir,
+      source_name,
+      base_url,
+      output_path,
+    } => {
+      let generated = generate_mod_source(source_dir, source_name, base_url, output_path).await?;
+      Ok(json!(generated))
+    }
+    CliCommand::SupportPackage {
+      output_path,
+      crash_report_id,
+    } => {
+      generate_support_package(
+        config(),
+        app_handle.clone(),
+        output_path.clone(),
+        crash_report_id,
+      )
+      .await?;
+      Ok(json!({ "path": output_path }))
+    }
+    CliCommand::Help => Ok(json!({ "usage": USAGE })),
+  }
+}
+
+// Runs the invocation to completion, returning the process' exit code
+pub async fn run(app_handle: &tauri::AppHandle, invocation: CliInvocation) -> i32 {
+  if let Some(install_dir) = invocation.install_dir {
+    let config = app_handle.state::<tokio::sync::Mutex<LauncherConfig>>();
+    let current = config.lock().await.installation_dir.clone();
+    if current.as_ref() != Some(&install_dir) {
+      if let Err(err) = set_install_directory(config, install_dir).await {
+        print_json(json!({ "success": false, "error": err.to_string() }));
+        return EXIT_FAILURE;
+      }
+    }
+  }
+  match run_command(app_handle, invocation.command).await {
+    Ok(result) => {
+      print_json(json!({ "success": true, "result": result }));
+      EXIT_SUCCESS
+    }
+    Err(err) => {
+      log::error!("Command failed: {}", err);
+      print_json(json!({ "success": false, "error": err.to_string() }));
+      EXIT_FAILURE
+    }
+  }
+}
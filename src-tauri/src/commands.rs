@@ -6,7 +6,9 @@ pub mod config;
 pub mod download;
 pub mod features;
 pub mod game;
+pub mod iso;
 pub mod logging;
+pub mod repl;
 pub mod support;
 pub mod util;
 pub mod versions;
@@ -42,6 +44,10 @@ pub enum CommandError {
   GameFeatures(String),
   #[error("{0}")]
   UnknownGame(String),
+  #[error("{0}")]
+  IsoLibrary(String),
+  #[error("{0}")]
+  Repl(String),
 }
 
 impl Serialize for CommandError {
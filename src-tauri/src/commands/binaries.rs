@@ -1,5 +1,8 @@
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "linux")]
+use crate::util::terminal::resolve_terminal;
 use std::{
   collections::HashMap,
   io::ErrorKind,
@@ -8,19 +11,30 @@ use std::{
   str::FromStr,
   time::Instant,
 };
-use tokio::{io::AsyncWriteExt, process::Command};
+use tokio::process::Command;
 
 use log::{info, warn};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use tauri::{Emitter, Manager};
 
 use crate::{
-  config::LauncherConfig,
+  compile_cache::{
+    forget_compiled_output_info, read_compiled_output_info, swap_compiled_output,
+    write_compiled_output_info, CompileCacheEntry,
+  },
+  config::{LaunchProfile, LauncherConfig, SupportedGame},
+  iso_library::{vanilla_reference, IsoLibrary},
+  process_registry::{wait_for_game_exit, ProcessRegistry, RunningGame},
+  repl::{start_goalc, ReplSessionInfo},
   util::{
-    file::overwrite_dir,
-    process::{create_log_file, create_std_log_file, watch_process},
+    disc_image::{is_bin_cue_image, prepare_disc_image},
+    file::{hash_dir_contents, overwrite_dir},
+    process::{
+      create_log_file, create_std_log_file, get_log_file_path, pipe_output_to_log, watch_process,
+    },
   },
   TAURI_APP,
 };
@@ -175,9 +189,70 @@ fn get_data_dir(
   Ok(data_folder)
 }
 
-struct ExecutableLocation {
-  executable_dir: PathBuf,
-  executable_path: PathBuf,
+// Determines which extracted game files the decompiler / compiler should use.  Images in the
+// shared ISO library are preferred, otherwise we fall back to the legacy `data/iso_data` folder
+//
+// Returns the path, and whether it is an already extracted folder that needs `--folder`
+fn resolve_iso_source(
+  config_info: &CommonConfigData,
+  game_name: &String,
+  path_to_iso: String,
+  data_folder: &Path,
+) -> (String, bool) {
+  let iso_library = IsoLibrary::load(&config_info.install_path);
+  let library_path = if path_to_iso.is_empty() {
+    iso_library.resolve_extracted_path(game_name, &vanilla_reference())
+  } else {
+    iso_library
+      .find_by_source(game_name, Path::new(&path_to_iso))
+      .filter(|image| image.is_extracted(iso_library.dir()))
+      .map(|image| image.extracted_path(iso_library.dir()))
+  };
+  if let Some(path) = library_path {
+    return (path.to_string_lossy().to_string(), true);
+  }
+  // BIN/CUE images were converted for extraction, only the extracted files can be used
+  if path_to_iso.is_empty() || is_bin_cue_image(Path::new(&path_to_iso)) {
+    return (
+      data_folder
+        .join("iso_data")
+        .join(game_name)
+        .to_string_lossy()
+        .to_string(),
+      false,
+    );
+  }
+  (path_to_iso, false)
+}
+
+// Fingerprint of everything that goes into a compile, if it matches the one recorded after the
+// last successful compile then the output is already up to date
+async fn compile_fingerprint(
+  tooling_version: &Version,
+  data_folder: &Path,
+  source_path: &str,
+) -> Result<String, std::io::Error> {
+  let tooling_version = tooling_version.to_string();
+  let data_folder = data_folder.to_path_buf();
+  let source_path = source_path.to_owned();
+  // the game files are large, so they are hashed off the async runtime
+  tokio::task::spawn_blocking(move || {
+    let mut hasher = Sha256::new();
+    hasher.update(tooling_version.as_bytes());
+    hasher.update(source_path.as_bytes());
+    hasher.update(b"goal_src");
+    hash_dir_contents(&mut hasher, &data_folder.join("goal_src"))?;
+    hasher.update(b"custom_assets");
+    hash_dir_contents(&mut hasher, &data_folder.join("custom_assets"))?;
+    Ok(format!("{:x}", hasher.finalize()))
+  })
+  .await
+  .map_err(std::io::Error::other)?
+}
+
+pub(crate) struct ExecutableLocation {
+  pub(crate) executable_dir: PathBuf,
+  pub(crate) executable_path: PathBuf,
 }
 
 fn get_exec_location(
@@ -229,6 +304,31 @@ pub async fn update_data_directory(
   })
 }
 
+fn register_library_image(
+  iso_library: &mut IsoLibrary,
+  game_name: &str,
+  path_to_iso: &str,
+  hash: &str,
+) -> Result<(), CommandError> {
+  let image = iso_library
+    .register(game_name, Path::new(path_to_iso), hash)
+    .and_then(|image| {
+      iso_library.add_reference(&image.id, &vanilla_reference())?;
+      Ok(image)
+    })
+    .map_err(|err| {
+      log::error!("Unable to register ISO in the library: {}", err);
+      CommandError::IsoLibrary(format!("Unable to register ISO in the library: {err}"))
+    })?;
+  log::info!(
+    "Registered ISO '{}' ({}) for {}",
+    image.id,
+    image.region.unwrap_or("unknown region".to_owned()),
+    game_name
+  );
+  Ok(())
+}
+
 #[tauri::command]
 pub async fn extract_and_validate_iso(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
@@ -236,7 +336,7 @@ pub async fn extract_and_validate_iso(
   path_to_iso: String,
   game_name: String,
 ) -> Result<InstallStepOutput, CommandError> {
-  let config_lock = config.lock().await;
+  let mut config_lock = config.lock().await;
   let config_info = common_prelude(&config_lock)?;
 
   let data_folder = get_data_dir(&config_info, &game_name, true)?;
@@ -255,8 +355,31 @@ pub async fn extract_and_validate_iso(
     }
   };
 
+  let mut iso_library = IsoLibrary::load(&config_info.install_path);
+
+  // BIN/CUE images have to be converted to a plain ISO first, the converted image is a temporary
+  // file that is cleaned up when this function returns
+  let source_path = Path::new(&path_to_iso);
+  let mut disc_image = None;
+  if is_bin_cue_image(source_path) && !iso_library.is_extracted_source(&game_name, source_path) {
+    disc_image = Some(
+      prepare_disc_image(
+        source_path,
+        &IsoLibrary::temp_dir(&config_info.install_path),
+      )
+      .map_err(|err| {
+        log::error!("Unable to convert disc image: {}", err);
+        CommandError::Installation(format!("Unable to convert disc image: {err}"))
+      })?,
+    );
+  }
+  let extractor_input = match &disc_image {
+    Some(image) => image.path().to_path_buf(),
+    None => source_path.to_path_buf(),
+  };
+
   let mut args = vec![
-    path_to_iso.clone(),
+    extractor_input.to_string_lossy().into_owned(),
     "--extract".to_string(),
     "--validate".to_string(),
     "--proj-path".to_string(),
@@ -266,9 +389,40 @@ pub async fn extract_and_validate_iso(
     args.push("--folder".to_string());
   }
   // Add new --game argument
+  // Versions that support it also support extracting into the shared ISO library
+  let mut prepared_image = None;
   if config_info.tooling_version.minor > 1 || config_info.tooling_version.patch >= 44 {
     args.push("--game".to_string());
     args.push(game_name.clone());
+
+    // nothing needs the config while the image is hashed
+    drop(config_lock);
+    let hash = iso_library
+      .image_hash(&game_name, source_path, &extractor_input)
+      .await;
+    config_lock = config.lock().await;
+    // another job may have changed the library in the meantime
+    iso_library = IsoLibrary::load(&config_info.install_path);
+    let hash = hash.map_err(|err| {
+      log::error!("Unable to inspect ISO: {}", err);
+      CommandError::IsoLibrary(format!("Unable to inspect ISO: {err}"))
+    })?;
+    let image = iso_library.prepare_extraction(&game_name, hash);
+    if image.already_extracted {
+      log::info!(
+        "ISO '{}' has already been extracted, re-using it for {}",
+        image.id,
+        game_name
+      );
+      register_library_image(&mut iso_library, &game_name, &path_to_iso, &image.hash)?;
+      return Ok(InstallStepOutput {
+        success: true,
+        msg: None,
+      });
+    }
+    args.push("--extract-path".to_string());
+    args.push(image.extraction_dir.to_string_lossy().into_owned());
+    prepared_image = Some(image);
   }
 
   log::info!("Running extractor with args: {:?}", args);
@@ -285,9 +439,14 @@ pub async fn extract_and_validate_iso(
   }
   let mut child = command.spawn()?;
 
-  // This is the first install step, reset the file
-  let mut log_file =
-    create_log_file(&app_handle, format!("extractor-{game_name}.log"), true).await?;
+  // This is the first install step, start a new log
+  let mut log_file = create_log_file(
+    &app_handle,
+    format!("extractor-{game_name}.log"),
+    false,
+    config_lock.log_retention_count,
+  )
+  .await?;
 
   let process_status = watch_process(&mut log_file, &mut child, &app_handle).await?;
   log_file.flush().await?;
@@ -295,6 +454,16 @@ pub async fn extract_and_validate_iso(
     Some(code) => {
       if code == 0 {
         log::info!("extraction and validation was successful");
+        match prepared_image {
+          Some(image) => {
+            register_library_image(&mut iso_library, &game_name, &path_to_iso, &image.hash)?;
+          }
+          None => {
+            // the legacy `iso_data` folder keeps the same path no matter which ISO was
+            // extracted into it, so the next compile can't be skipped
+            forget_compiled_output_info(&data_folder.join("out"))?;
+          }
+        }
         return Ok(InstallStepOutput {
           success: true,
           msg: None,
@@ -349,14 +518,8 @@ pub async fn run_decompiler(
     }
   };
 
-  let mut source_path = path_to_iso;
-  if source_path.is_empty() {
-    source_path = data_folder
-      .join("iso_data")
-      .join(&game_name)
-      .to_string_lossy()
-      .to_string();
-  }
+  let (source_path, source_is_folder) =
+    resolve_iso_source(&config_info, &game_name, path_to_iso, &data_folder);
 
   let mut command = Command::new(exec_info.executable_path);
 
@@ -395,6 +558,9 @@ pub async fn run_decompiler(
     "--proj-path".to_string(),
     data_folder.to_string_lossy().into_owned(),
   ];
+  if source_is_folder {
+    args.push("--folder".to_string());
+  }
 
   // Add new --game argument
   if config_info.tooling_version.minor > 1 || config_info.tooling_version.patch >= 44 {
@@ -426,6 +592,7 @@ pub async fn run_decompiler(
     &app_handle,
     format!("extractor-{game_name}.log"),
     !truncate_logs,
+    config_lock.log_retention_count,
   )
   .await?;
 
@@ -489,21 +656,71 @@ pub async fn run_compiler(
     }
   };
 
-  let mut source_path = path_to_iso;
-  if source_path.is_empty() {
-    source_path = data_folder
-      .join("iso_data")
-      .join(&game_name)
-      .to_string_lossy()
-      .to_string();
+  let (source_path, source_is_folder) =
+    resolve_iso_source(&config_info, &game_name, path_to_iso, &data_folder);
+
+  let out_dir = data_folder.join("out");
+  let cache_size_limit = config_lock.compiled_output_cache_size_mb * 1024 * 1024;
+  // nothing needs the config while the compile inputs are hashed and the outputs shuffled around
+  drop(config_lock);
+  let fingerprint =
+    match compile_fingerprint(&config_info.tooling_version, &data_folder, &source_path).await {
+      Ok(fingerprint) => Some(fingerprint),
+      Err(err) => {
+        log::warn!("Unable to fingerprint compile inputs, will compile regardless: {err}");
+        None
+      }
+    };
+  let previous_output = read_compiled_output_info(&out_dir);
+  if let (Some(fingerprint), Some(previous_output)) = (&fingerprint, &previous_output) {
+    if &previous_output.fingerprint == fingerprint {
+      log::info!("compile inputs are unchanged since the last successful compile, skipping");
+      return Ok(InstallStepOutput {
+        success: true,
+        msg: None,
+      });
+    }
   }
 
+  // compiled outputs can be gigabytes, so they are cached and restored off the async runtime
+  let restored = {
+    let install_path = config_info.install_path.clone();
+    let game_name = game_name.clone();
+    let out_dir = out_dir.clone();
+    let fingerprint = fingerprint.clone();
+    tokio::task::spawn_blocking(move || {
+      swap_compiled_output(
+        &install_path,
+        &game_name,
+        &out_dir,
+        fingerprint.as_deref(),
+        previous_output,
+        cache_size_limit,
+      )
+    })
+    .await
+    .map_err(|err| {
+      CommandError::BinaryExecution(format!("Unable to prepare the compiled output: {err}"))
+    })??
+  };
+  if restored {
+    log::info!("restored previously compiled output, skipping compilation");
+    return Ok(InstallStepOutput {
+      success: true,
+      msg: None,
+    });
+  }
+  let config_lock = config.lock().await;
+
   let mut args = vec![
     source_path,
     "--compile".to_string(),
     "--proj-path".to_string(),
     data_folder.to_string_lossy().into_owned(),
   ];
+  if source_is_folder {
+    args.push("--folder".to_string());
+  }
   // Add new --game argument
   if config_info.tooling_version.minor > 1 || config_info.tooling_version.patch >= 44 {
     args.push("--game".to_string());
@@ -528,6 +745,7 @@ pub async fn run_compiler(
     &app_handle,
     format!("extractor-{game_name}.log"),
     !truncate_logs,
+    config_lock.log_retention_count,
   )
   .await?;
 
@@ -537,6 +755,24 @@ pub async fn run_compiler(
     Some(code) => {
       if code == 0 {
         log::info!("compilation was successful");
+        if let Some(fingerprint) = fingerprint {
+          let texture_packs = SupportedGame::from_str(&game_name)
+            .ok()
+            .and_then(|game| config_lock.games.get(&game))
+            .map(|game_config| game_config.active_texture_packs())
+            .unwrap_or_default();
+          let output_info = CompileCacheEntry {
+            fingerprint,
+            game: game_name.clone(),
+            tooling_version: config_info.active_version.clone(),
+            texture_packs,
+            size: 0,
+            last_used: chrono::Utc::now().to_rfc3339(),
+          };
+          if let Err(err) = write_compiled_output_info(&out_dir, &output_info) {
+            log::warn!("Unable to record compile fingerprint: {err}");
+          }
+        }
         return Ok(InstallStepOutput {
           success: true,
           msg: None,
@@ -592,10 +828,27 @@ pub async fn open_repl(
   }
   #[cfg(target_os = "linux")]
   {
-    command = std::process::Command::new("xdg-terminal-exec");
-    command
-      .args(["./goalc", "--proj-path", &data_folder.to_string_lossy()])
-      .current_dir(exec_info.executable_dir);
+    let terminal = match resolve_terminal(config_lock.preferred_terminal.as_deref()) {
+      Some(terminal) => terminal,
+      None => {
+        let _ = app_handle.emit(
+          "toast_msg",
+          ToastPayload {
+            toast: "No terminal emulator found, set one in the launcher's settings".to_owned(),
+            level: "error".to_string(),
+          },
+        );
+        return Err(CommandError::BinaryExecution(
+          "Unable to find a terminal to launch the REPL in".to_owned(),
+        ));
+      }
+    };
+    log::info!("Opening REPL in {}", terminal.path.display());
+    command = terminal.command(
+      &exec_info.executable_path,
+      &["--proj-path", &data_folder.to_string_lossy()],
+    );
+    command.current_dir(exec_info.executable_dir);
   }
   #[cfg(target_os = "macos")]
   {
@@ -630,11 +883,87 @@ pub async fn open_repl(
   }
 }
 
+#[tauri::command]
+pub async fn start_repl(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  port: Option<u16>,
+) -> Result<ReplSessionInfo, CommandError> {
+  let config_lock = config.lock().await;
+  let config_info = common_prelude(&config_lock)?;
+  let data_folder = get_data_dir(&config_info, &game_name, false)?;
+  let exec_info = get_exec_location(&config_info, "goalc")?;
+  drop(config_lock);
+  start_goalc(
+    &app_handle,
+    &exec_info.executable_dir,
+    vec![
+      "--proj-path".to_owned(),
+      data_folder.to_string_lossy().into_owned(),
+    ],
+    &game_name,
+    None,
+    port,
+  )
+  .await
+  .map_err(|err| {
+    log::error!("Unable to start REPL: {}", err);
+    CommandError::Repl(format!("Unable to start REPL: {err}"))
+  })
+}
+
+// Looks up the requested launch profile, no profile means launching with the defaults
+pub fn resolve_launch_profile(
+  config: &LauncherConfig,
+  game_name: &str,
+  mod_info: Option<(&str, &str)>,
+  launch_profile: &Option<String>,
+) -> Result<LaunchProfile, CommandError> {
+  match launch_profile {
+    Some(profile_name) => config
+      .get_launch_profile(game_name, mod_info, profile_name)
+      .map_err(|err| CommandError::Configuration(err.to_string())),
+    None => Ok(LaunchProfile::default()),
+  }
+}
+
+pub(crate) fn custom_exec_location(
+  custom_exec_location: &str,
+) -> Result<ExecutableLocation, CommandError> {
+  match PathBuf::from_str(custom_exec_location) {
+    Ok(exec_path) if !exec_path.is_file() => {
+      log::error!(
+        "Could not find the custom binary '{}', can't perform operation",
+        exec_path.to_string_lossy()
+      );
+      Err(CommandError::BinaryExecution(format!(
+        "Could not find the custom binary '{}', can't perform operation",
+        exec_path.to_string_lossy()
+      )))
+    }
+    Ok(exec_path) => match exec_path.parent() {
+      Some(exec_dir) => Ok(ExecutableLocation {
+        executable_dir: exec_dir.to_path_buf(),
+        executable_path: exec_path.clone(),
+      }),
+      None => Err(CommandError::BinaryExecution(
+        "Failed to resolve custom binary parent directory".to_string(),
+      )),
+    },
+    Err(err) => Err(CommandError::BinaryExecution(format!(
+      "Failed to resolve custom binary location {}",
+      err
+    ))),
+  }
+}
+
 fn generate_launch_game_string(
   config_info: &CommonConfigData,
   game_name: String,
   in_debug: bool,
   quote_project_path: bool,
+  launch_profile: &LaunchProfile,
 ) -> Result<Vec<String>, CommandError> {
   let data_folder = get_data_dir(config_info, &game_name, false)?;
 
@@ -674,23 +1003,30 @@ fn generate_launch_game_string(
       args.push("-debug".to_string());
     }
   }
-  Ok(args)
+  Ok(launch_profile.apply_args(args))
 }
 
 #[tauri::command]
 pub async fn get_launch_game_string(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
   game_name: String,
+  launch_profile: Option<String>,
 ) -> Result<String, CommandError> {
   let config_lock = config.lock().await;
   let config_info = common_prelude(&config_lock)?;
+  let profile = resolve_launch_profile(&config_lock, &game_name, None, &launch_profile)?;
 
-  let exec_info = get_exec_location(&config_info, "gk")?;
-  let args = generate_launch_game_string(&config_info, game_name, false, true)?;
+  let exec_info = match &profile.executable_path {
+    Some(executable_path) => custom_exec_location(executable_path)?,
+    None => get_exec_location(&config_info, "gk")?,
+  };
+  let args = generate_launch_game_string(&config_info, game_name, false, true, &profile)?;
+  let (program, args) = profile.command_line(&exec_info.executable_path, args);
 
   Ok(format!(
-    "{} {}",
-    exec_info.executable_path.display(),
+    "{}{} {}",
+    profile.env_prefix(),
+    program.display(),
     args.join(" ")
   ))
 }
@@ -702,35 +1038,34 @@ pub async fn launch_game(
   game_name: String,
   in_debug: bool,
   executable_location: Option<String>,
+  launch_profile: Option<String>,
+  allow_concurrent: Option<bool>,
 ) -> Result<(), CommandError> {
   let config_lock = config.lock().await;
   let config_info = common_prelude(&config_lock)?;
 
-  let mut exec_info = get_exec_location(&config_info, "gk")?;
-  if let Some(custom_exec_location) = executable_location {
-    match PathBuf::from_str(custom_exec_location.as_str()) {
-      Ok(exec_path) => {
-        let path_copy = exec_path.clone();
-        if path_copy.parent().is_none() {
-          return Err(CommandError::BinaryExecution(
-            "Failed to resolve custom binary parent directory".to_string(),
-          ));
-        }
-        exec_info = ExecutableLocation {
-          executable_dir: exec_path.clone().parent().unwrap().to_path_buf(),
-          executable_path: exec_path.clone(),
-        };
-      }
-      Err(err) => {
-        return Err(CommandError::BinaryExecution(format!(
-          "Failed to resolve custom binary location {}",
-          err
-        )));
-      }
+  let registry_state = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+  let mut registry = registry_state.lock().await;
+  if let Some(running) = registry.find(&game_name, None) {
+    if !allow_concurrent.unwrap_or(false) {
+      return Err(CommandError::GameManagement(format!(
+        "{} is already running (pid {})",
+        game_name, running.pid
+      )));
     }
   }
 
-  let args = generate_launch_game_string(&config_info, game_name.clone(), in_debug, false)?;
+  let profile = resolve_launch_profile(&config_lock, &game_name, None, &launch_profile)?;
+
+  // an explicitly provided executable wins over the one in the profile
+  let exec_info = match executable_location.or_else(|| profile.executable_path.clone()) {
+    Some(custom_location) => custom_exec_location(&custom_location)?,
+    None => get_exec_location(&config_info, "gk")?,
+  };
+
+  let args =
+    generate_launch_game_string(&config_info, game_name.clone(), in_debug, false, &profile)?;
+  let (program, args) = profile.command_line(&exec_info.executable_path, args);
 
   log::info!(
     "Launching game version {:?} -> {:?} with args: {:?}. Working Directory: {:?}, Path: {:?}",
@@ -738,16 +1073,24 @@ pub async fn launch_game(
     &config_info.tooling_version,
     args,
     exec_info.executable_dir,
-    exec_info.executable_path,
+    program,
   );
 
-  let log_file = create_std_log_file(&app_handle, format!("game-{game_name}.log"), false)?;
+  let log_name = registry.free_log_name(&format!("game-{game_name}.log"));
+  let log_path = get_log_file_path(&app_handle, &log_name);
+  let log_file = create_std_log_file(
+    &app_handle,
+    log_name,
+    false,
+    config_lock.log_retention_count,
+  )?;
 
-  let mut command = std::process::Command::new(exec_info.executable_path);
+  let mut command = std::process::Command::new(program);
   command
     .args(args)
-    .stdout(log_file.try_clone().unwrap())
-    .stderr(log_file)
+    .envs(&profile.env_vars)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
     .current_dir(exec_info.executable_dir);
   #[cfg(windows)]
   {
@@ -755,13 +1098,29 @@ pub async fn launch_game(
   }
   // Start the process here so if there is an error, we can return immediately
   let mut child = command.spawn()?;
+  pipe_output_to_log(&mut child, log_file);
+  let pid = child.id();
+  registry.register(
+    &app_handle,
+    child,
+    RunningGame::new(
+      pid,
+      &game_name,
+      None,
+      Some(config_info.active_version.clone()),
+      log_path,
+    ),
+  );
+  drop(registry);
   // if all goes well, we await the child to exit in the background (separate thread)
   tokio::spawn(async move {
     let start_time = Instant::now(); // get the start time of the game
                                      // start waiting for the game to exit
-    match child.wait() {
-      Ok(status_code) => {
-        if status_code.code().is_none() || status_code.code().unwrap() != 0 {
+    match wait_for_game_exit(&app_handle, pid).await {
+      Ok(exit) => {
+        if !exit.stop_requested
+          && (exit.status.code().is_none() || exit.status.code().unwrap() != 0)
+        {
           let _ = app_handle.emit(
             "toast_msg",
             ToastPayload {
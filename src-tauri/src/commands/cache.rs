@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 
+use futures_util::future::join_all;
+
 use crate::{
   cache::{LauncherCache, ModSourceData},
   config::LauncherConfig,
+  image_cache::{download_image, ImageCache},
+  mod_search::{search_mods, ModSearchQuery, ModSearchResult},
 };
 
 use super::CommandError;
@@ -29,3 +33,67 @@ pub async fn get_mod_sources_data(
   let cache_lock = cache.lock().await;
   Ok(cache_lock.mod_sources.clone())
 }
+
+// A page of the mods across every cached mod source that match the query
+#[tauri::command]
+pub async fn search_cached_mods(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  query: ModSearchQuery,
+) -> Result<ModSearchResult, CommandError> {
+  let cache_lock = cache.lock().await;
+  let config_lock = config.lock().await;
+  let installed = query
+    .game
+    .as_deref()
+    .and_then(|game| config_lock.game_config(game))
+    .map(|game_config| game_config.mods_installed_version.clone())
+    .unwrap_or_default();
+  search_mods(&cache_lock, &installed, &query).map_err(|err| {
+    log::error!("Unable to search mods: {}", err);
+    CommandError::Cache(format!("Unable to search mods: {}", err))
+  })
+}
+
+// Local copies of remote mod art, keyed by url, that can be loaded through the asset protocol.
+// Art that isn't cached yet is downloaded, anything that can't be is left out so the url can be
+// used as is.
+#[tauri::command]
+pub async fn get_cached_mod_art(
+  image_cache: tauri::State<'_, tokio::sync::Mutex<ImageCache>>,
+  urls: Vec<String>,
+) -> Result<HashMap<String, String>, CommandError> {
+  let mut cached = HashMap::new();
+  let mut missing = Vec::new();
+  {
+    let cache_lock = image_cache.lock().await;
+    for url in urls {
+      if !url.starts_with("http://") && !url.starts_with("https://") {
+        continue;
+      }
+      match cache_lock.cached_path(&url) {
+        Some(path) => {
+          cached.insert(url, path.to_string_lossy().into_owned());
+        }
+        None if !missing.contains(&url) => missing.push(url),
+        None => {}
+      }
+    }
+  }
+  if missing.is_empty() {
+    return Ok(cached);
+  }
+
+  // downloaded without holding the lock so the mod list isn't held up by other requests
+  let downloads = join_all(missing.iter().map(|url| download_image(url))).await;
+  let mut cache_lock = image_cache.lock().await;
+  for (url, download) in missing.into_iter().zip(downloads) {
+    match download.and_then(|contents| cache_lock.store(&url, &contents)) {
+      Ok(path) => {
+        cached.insert(url, path.to_string_lossy().into_owned());
+      }
+      Err(err) => log::warn!("Unable to cache mod art from {}: {}", url, err),
+    }
+  }
+  Ok(cached)
+}
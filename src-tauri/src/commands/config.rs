@@ -1,7 +1,8 @@
 use super::{util::is_avx_supported, CommandError};
-use crate::config::LauncherConfig;
+use crate::config::{LaunchProfile, LauncherConfig};
 use semver::Version;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 
 #[tauri::command]
 pub async fn reset_to_defaults(
@@ -80,6 +81,71 @@ pub async fn update_mods_setting_value(
   }
 }
 
+// Launch profiles belong to a mod when both the mod and its source are provided
+pub fn launch_profile_mod_info<'a>(
+  mod_name: &'a Option<String>,
+  source_name: &'a Option<String>,
+) -> Option<(&'a str, &'a str)> {
+  match (source_name, mod_name) {
+    (Some(source_name), Some(mod_name)) => Some((source_name.as_str(), mod_name.as_str())),
+    _ => None,
+  }
+}
+
+#[tauri::command]
+pub async fn get_launch_profiles(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  mod_name: Option<String>,
+  source_name: Option<String>,
+) -> Result<HashMap<String, LaunchProfile>, CommandError> {
+  let config_lock = config.lock().await;
+  let mod_info = launch_profile_mod_info(&mod_name, &source_name);
+  Ok(
+    config_lock
+      .game_config(&game_name)
+      .map(|game_config| game_config.launch_profiles_for(mod_info))
+      .unwrap_or_default(),
+  )
+}
+
+#[tauri::command]
+pub async fn save_launch_profile(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  profile_name: String,
+  profile: LaunchProfile,
+  mod_name: Option<String>,
+  source_name: Option<String>,
+) -> Result<(), CommandError> {
+  let mut config_lock = config.lock().await;
+  let mod_info = launch_profile_mod_info(&mod_name, &source_name);
+  config_lock
+    .save_launch_profile(&game_name, mod_info, profile_name, profile)
+    .map_err(|err| {
+      log::error!("Unable to save launch profile: {:?}", err);
+      CommandError::Configuration("Unable to save launch profile".to_owned())
+    })
+}
+
+#[tauri::command]
+pub async fn delete_launch_profile(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  profile_name: String,
+  mod_name: Option<String>,
+  source_name: Option<String>,
+) -> Result<(), CommandError> {
+  let mut config_lock = config.lock().await;
+  let mod_info = launch_profile_mod_info(&mod_name, &source_name);
+  config_lock
+    .delete_launch_profile(&game_name, mod_info, &profile_name)
+    .map_err(|err| {
+      log::error!("Unable to delete launch profile: {:?}", err);
+      CommandError::Configuration("Unable to delete launch profile".to_owned())
+    })
+}
+
 #[tauri::command]
 pub async fn set_install_directory(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
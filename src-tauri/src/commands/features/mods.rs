@@ -1,34 +1,135 @@
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "linux")]
+use crate::util::terminal::resolve_terminal;
 use std::{
   io::ErrorKind,
   path::{Path, PathBuf},
   process::Stdio,
+  time::Instant,
 };
 
 use serde::{Deserialize, Serialize};
-use tauri::Emitter;
-use tokio::{io::AsyncWriteExt, process::Command};
+use tauri::{Emitter, Manager};
+use tokio::process::Command;
 
 use crate::{
-  commands::{binaries::InstallStepOutput, CommandError},
-  config::LauncherConfig,
+  cache::{LauncherCache, ModVersion},
+  commands::{
+    binaries::{
+      custom_exec_location, resolve_launch_profile, ExecutableLocation, InstallStepOutput,
+    },
+    CommandError,
+  },
+  config::{LaunchProfile, LauncherConfig},
+  iso_library::{mod_reference, vanilla_reference, IsoLibrary},
+  mod_dependencies::{
+    dependents, is_newer_version, resolve_install_plan, with_sources, PlannedModInstall,
+  },
+  mod_packaging::{
+    mod_art_path, package_mod, read_installed_manifest, validate_manifest, ModManifest, ModPackage,
+    ModPackageOptions, COVER_FILE_NAME, MANIFEST_FILE_NAME, THUMBNAIL_FILE_NAME,
+  },
+  mod_source_generator::{self, GeneratedModSource},
+  mod_versions::{
+    has_previous_version, keep_installed_version, previous_versions, prune_previous_versions,
+    restore_previous_version,
+  },
+  process_registry::{wait_for_game_exit, ProcessRegistry, RunningGame},
+  repl::{start_goalc, ReplSessionInfo},
   util::{
-    file::{create_dir, delete_dir, to_image_base64},
+    archive::{
+      archive_name, emit_extraction_progress, extract_and_delete_archive, extract_archive,
+      read_archive_file,
+    },
+    disc_image::{is_bin_cue_image, prepare_disc_image},
+    file::{create_dir, delete_dir, is_link, link_dir, remove_dir_link, to_image_base64},
     network::download_file,
-    process::{create_log_file, create_std_log_file, watch_process},
-    tar::{extract_and_delete_tar_ball, extract_tar_ball},
-    zip::{extract_and_delete_zip_file, extract_zip_file},
+    process::{
+      create_log_file, create_std_log_file, get_log_file_path, pipe_output_to_log, watch_process,
+    },
   },
 };
 
+// Clears the way for a new install of a mod, the installed version is kept so it can be switched
+// back to, or restored if the install fails
+fn set_aside_installed_mod(
+  config: &LauncherConfig,
+  mod_dir: &Path,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+) -> Result<(), CommandError> {
+  match config.installed_mod_version(game_name, source_name, mod_name) {
+    Some(installed_version) => keep_installed_version(mod_dir, &installed_version).map_err(|err| {
+      log::error!("Unable to keep the installed version of the mod: {}", err);
+      CommandError::GameFeatures(format!(
+        "Unable to keep the installed version of the mod: {}",
+        err
+      ))
+    }),
+    None => Ok(delete_dir(mod_dir)?),
+  }
+}
+
+// Puts back the version that was installed before a failed install, returns whether there was one
+fn rollback_installed_mod(
+  config: &LauncherConfig,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+) -> Result<bool, CommandError> {
+  let install_path = match &config.installation_dir {
+    None => {
+      return Err(CommandError::GameFeatures(
+        "No installation directory set, can't roll back mod".to_string(),
+      ))
+    }
+    Some(path) => Path::new(path),
+  };
+  let mod_dir = install_path
+    .join("features")
+    .join(game_name)
+    .join("mods")
+    .join(source_name)
+    .join(mod_name);
+  let installed_version = match config.installed_mod_version(game_name, source_name, mod_name) {
+    Some(version) if has_previous_version(&mod_dir, &version) => version,
+    _ => return Ok(false),
+  };
+  log::warn!(
+    "Rolling {} from {} back to version {}",
+    mod_name,
+    source_name,
+    installed_version
+  );
+  restore_previous_version(&mod_dir, &installed_version).map_err(|err| {
+    log::error!("Unable to roll back mod: {}", err);
+    CommandError::GameFeatures(format!("Unable to roll back mod: {}", err))
+  })?;
+  Ok(true)
+}
+
+// manifests are tiny, anything bigger isn't one
+const MAX_MANIFEST_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedMod {
+  pub mod_name: String,
+  pub version: String,
+  pub manifest: Option<ModManifest>,
+}
+
 #[tauri::command]
 pub async fn extract_new_mod(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
   game_name: String,
   bundle_path: String,
   mod_source: String,
-) -> Result<InstallStepOutput, CommandError> {
+) -> Result<ImportedMod, CommandError> {
   let config_lock = config.lock().await;
   let install_path = match &config_lock.installation_dir {
     None => {
@@ -39,51 +140,78 @@ pub async fn extract_new_mod(
     Some(path) => Path::new(path),
   };
 
-  // The name of the zip becomes the folder, if one already exists it will be deleted!
+  // The mod's id from its manifest becomes the folder, or the name of the archive for mods that
+  // don't have one.  The installed version is kept aside, see `set_aside_installed_mod`
   let bundle_path_buf = PathBuf::from(bundle_path);
-  let mod_name = match bundle_path_buf.file_stem() {
-    Some(name) => name.to_string_lossy().to_string(),
-    None => {
+  let manifest = read_archive_file(&bundle_path_buf, MANIFEST_FILE_NAME, MAX_MANIFEST_SIZE)
+    .map_err(|err| {
+      log::error!("Unable to read mod archive: {}", err);
+      CommandError::GameFeatures(format!("Unable to read mod archive: {}", err))
+    })?
+    .map(|contents| {
+      let manifest: ModManifest = serde_json::from_slice(&contents).map_err(|err| {
+        CommandError::GameFeatures(format!("The mod's manifest is invalid: {}", err))
+      })?;
+      validate_manifest(&manifest, &game_name)
+        .map_err(|err| CommandError::GameFeatures(err.to_string()))?;
+      Ok::<ModManifest, CommandError>(manifest)
+    })
+    .transpose()?;
+  let mod_name = match (&manifest, archive_name(&bundle_path_buf)) {
+    (Some(manifest), _) => manifest.id.trim().to_owned(),
+    (None, Some(name)) => name,
+    (None, None) => {
       return Err(CommandError::GameFeatures(
-        "Unable to get mod name from zip file path".to_string(),
+        "Unable to get mod name from archive file path".to_string(),
       ));
     }
   };
+  let version = manifest
+    .as_ref()
+    .map(|manifest| manifest.version.trim().to_owned())
+    .unwrap_or_else(|| "local".to_owned());
   let destination_dir = &install_path
     .join("features")
-    .join(game_name)
+    .join(&game_name)
     .join("mods")
-    .join(mod_source)
+    .join(&mod_source)
     .join(&mod_name);
-  delete_dir(destination_dir)?;
+  set_aside_installed_mod(
+    &config_lock,
+    destination_dir,
+    &game_name,
+    &mod_source,
+    &mod_name,
+  )?;
   create_dir(destination_dir).map_err(|err| {
     log::error!("Unable to create directory for mod: {}", err);
     CommandError::GameFeatures(format!("Unable to create directory for mod: {}", err))
   })?;
-  if cfg!(windows) {
-    extract_zip_file(&bundle_path_buf, destination_dir, false).map_err(|err| {
-      log::error!("Unable to extract mod: {}", err);
-      CommandError::GameFeatures(format!("Unable to extract mod: {}", err))
-    })?;
-  } else if cfg!(unix) {
-    extract_tar_ball(&bundle_path_buf, destination_dir).map_err(|err| {
-      log::error!("Unable to extract mod: {}", err);
-      CommandError::GameFeatures(format!("Unable to extract mod: {}", err))
-    })?;
-  } else {
-    Err(CommandError::VersionManagement(
-      "Unknown operating system, unable to download and extract mod".to_owned(),
-    ))?;
+  if let Err(err) = extract_archive(
+    &bundle_path_buf,
+    destination_dir,
+    false,
+    &mut emit_extraction_progress(&app_handle),
+  ) {
+    log::error!("Unable to extract mod: {}", err);
+    rollback_installed_mod(&config_lock, &game_name, &mod_source, &mod_name)?;
+    return Err(CommandError::GameFeatures(format!(
+      "Unable to extract mod: {}",
+      err
+    )));
   }
-  Ok(InstallStepOutput {
-    success: true,
-    msg: None,
+  log::info!("Imported {} version {}", mod_name, version);
+  Ok(ImportedMod {
+    mod_name,
+    version,
+    manifest,
   })
 }
 
 #[tauri::command]
 pub async fn download_and_extract_new_mod(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
   game_name: String,
   download_url: String,
   mod_name: String,
@@ -102,13 +230,20 @@ pub async fn download_and_extract_new_mod(
   // Download the file
   let parent_path = &install_path
     .join("features")
-    .join(game_name)
+    .join(&game_name)
     .join("mods")
     .join(&source_name)
     .join(&mod_name);
-  let download_path = &parent_path.join(format!("{mod_name}.zip"));
+  // the archive format is detected after downloading, so the name is only temporary
+  let download_path = &parent_path.join(format!("{mod_name}.download"));
 
-  delete_dir(parent_path)?;
+  set_aside_installed_mod(
+    &config_lock,
+    parent_path,
+    &game_name,
+    &source_name,
+    &mod_name,
+  )?;
   create_dir(parent_path).map_err(|err| {
     log::error!("Unable to create directory for mod: {}", err);
     CommandError::GameFeatures(format!("Unable to create directory for mod: {}", err))
@@ -127,21 +262,16 @@ pub async fn download_and_extract_new_mod(
       )
     })?;
 
-  if cfg!(windows) {
-    extract_and_delete_zip_file(download_path, parent_path, false).map_err(|err| {
-      log::error!("Unable to extract mod: {}", err);
-      CommandError::GameFeatures(format!("Unable to extract mod: {}", err))
-    })?;
-  } else if cfg!(unix) {
-    extract_and_delete_tar_ball(download_path, parent_path).map_err(|err| {
-      log::error!("Unable to extract mod: {}", err);
-      CommandError::GameFeatures(format!("Unable to extract mod: {}", err))
-    })?;
-  } else {
-    Err(CommandError::VersionManagement(
-      "Unknown operating system, unable to download and extract mod".to_owned(),
-    ))?;
-  }
+  extract_and_delete_archive(
+    download_path,
+    parent_path,
+    false,
+    &mut emit_extraction_progress(&app_handle),
+  )
+  .map_err(|err| {
+    log::error!("Unable to extract mod: {}", err);
+    CommandError::GameFeatures(format!("Unable to extract mod: {}", err))
+  })?;
 
   Ok(InstallStepOutput {
     success: true,
@@ -163,6 +293,12 @@ pub async fn base_game_iso_exists(
     }
     Some(path) => Path::new(path),
   };
+  if IsoLibrary::load(install_path)
+    .resolve(&game_name, &vanilla_reference())
+    .is_some()
+  {
+    return Ok(true);
+  }
   Ok(
     install_path
       .join("active")
@@ -174,6 +310,29 @@ pub async fn base_game_iso_exists(
   )
 }
 
+// Mods share the extracted base game with the vanilla install via the ISO library, mods that
+// were installed without providing an ISO use the vanilla install's, and older installs may
+// still only have it in the vanilla game's `iso_data` folder
+fn get_mod_iso_dir(
+  install_path: &Path,
+  game_name: &str,
+  mod_name: &str,
+  source_name: &str,
+) -> PathBuf {
+  let iso_library = IsoLibrary::load(install_path);
+  iso_library
+    .resolve_extracted_path(game_name, &mod_reference(source_name, mod_name))
+    .or_else(|| iso_library.resolve_extracted_path(game_name, &vanilla_reference()))
+    .unwrap_or_else(|| {
+      install_path
+        .join("active")
+        .join(game_name)
+        .join("data")
+        .join("iso_data")
+        .join(game_name)
+    })
+}
+
 fn bin_ext(filename: &str) -> String {
   if cfg!(windows) {
     return format!("{filename}.exe");
@@ -181,11 +340,6 @@ fn bin_ext(filename: &str) -> String {
   filename.to_string()
 }
 
-struct ExecutableLocation {
-  executable_dir: PathBuf,
-  executable_path: PathBuf,
-}
-
 fn get_mod_exec_location(
   install_path: std::path::PathBuf,
   executable_name: &str,
@@ -221,6 +375,23 @@ struct LauncherErrorCode {
   msg: String,
 }
 
+fn register_mod_library_image(
+  iso_library: &mut IsoLibrary,
+  game_name: &str,
+  mod_name: &str,
+  source_name: &str,
+  path_to_iso: &str,
+  hash: &str,
+) -> Result<(), CommandError> {
+  iso_library
+    .register(game_name, Path::new(path_to_iso), hash)
+    .and_then(|image| iso_library.add_reference(&image.id, &mod_reference(source_name, mod_name)))
+    .map_err(|err| {
+      log::error!("Unable to register ISO in the library: {}", err);
+      CommandError::IsoLibrary(format!("Unable to register ISO in the library: {err}"))
+    })
+}
+
 #[tauri::command]
 pub async fn extract_iso_for_mod_install(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
@@ -230,15 +401,16 @@ pub async fn extract_iso_for_mod_install(
   source_name: String,
   path_to_iso: String,
 ) -> Result<InstallStepOutput, CommandError> {
-  let config_lock = config.lock().await;
+  let mut config_lock = config.lock().await;
   let install_path = match &config_lock.installation_dir {
     None => {
       return Err(CommandError::GameFeatures(
         "No installation directory set, can't extract mod".to_string(),
       ))
     }
-    Some(path) => Path::new(path),
+    Some(path) => PathBuf::from(path),
   };
+  let install_path = install_path.as_path();
   let exec_info = match get_mod_exec_location(
     install_path.to_path_buf(),
     "extractor",
@@ -256,17 +428,63 @@ pub async fn extract_iso_for_mod_install(
     }
   };
 
-  let iso_extraction_dir = install_path
-    .join("active")
-    .join(&game_name)
-    .join("data")
-    .join("iso_data")
-    .to_path_buf();
+  let mut iso_library = IsoLibrary::load(install_path);
+
+  // BIN/CUE images have to be converted to a plain ISO first, the converted image is a temporary
+  // file that is cleaned up when this function returns
+  let source_path = Path::new(&path_to_iso);
+  let mut disc_image = None;
+  if is_bin_cue_image(source_path) && !iso_library.is_extracted_source(&game_name, source_path) {
+    disc_image = Some(
+      prepare_disc_image(source_path, &IsoLibrary::temp_dir(install_path)).map_err(|err| {
+        log::error!("Unable to convert disc image: {}", err);
+        CommandError::GameFeatures(format!("Unable to convert disc image: {err}"))
+      })?,
+    );
+  }
+  let extractor_input = match &disc_image {
+    Some(image) => image.path().to_path_buf(),
+    None => source_path.to_path_buf(),
+  };
+
+  // nothing needs the config while the image is hashed
+  drop(config_lock);
+  let hash = iso_library
+    .image_hash(&game_name, source_path, &extractor_input)
+    .await;
+  config_lock = config.lock().await;
+  // another job may have changed the library in the meantime
+  iso_library = IsoLibrary::load(install_path);
+  let hash = hash.map_err(|err| {
+    log::error!("Unable to inspect ISO: {}", err);
+    CommandError::IsoLibrary(format!("Unable to inspect ISO: {err}"))
+  })?;
+  let image = iso_library.prepare_extraction(&game_name, hash);
+  if image.already_extracted {
+    log::info!(
+      "ISO '{}' has already been extracted, re-using it for {}",
+      image.id,
+      mod_name
+    );
+    register_mod_library_image(
+      &mut iso_library,
+      &game_name,
+      &mod_name,
+      &source_name,
+      &path_to_iso,
+      &image.hash,
+    )?;
+    return Ok(InstallStepOutput {
+      success: true,
+      msg: None,
+    });
+  }
+  let iso_extraction_dir = image.extraction_dir.clone();
 
   create_dir(&iso_extraction_dir)?;
 
   let args = vec![
-    path_to_iso.clone(),
+    extractor_input.to_string_lossy().into_owned(),
     "--extract".to_string(),
     "--validate".to_string(),
     "--extract-path".to_string(),
@@ -289,11 +507,12 @@ pub async fn extract_iso_for_mod_install(
   }
   let mut child = command.spawn()?;
 
-  // This is the first install step, reset the file
+  // This is the first install step, start a new log
   let mut log_file = create_log_file(
     &app_handle,
     format!("extractor-{game_name}-{mod_name}.log"),
-    true,
+    false,
+    config_lock.log_retention_count,
   )
   .await?;
 
@@ -302,6 +521,14 @@ pub async fn extract_iso_for_mod_install(
     Some(code) => {
       if code == 0 {
         log::info!("extraction and validation was successful");
+        register_mod_library_image(
+          &mut iso_library,
+          &game_name,
+          &mod_name,
+          &source_name,
+          &path_to_iso,
+          &image.hash,
+        )?;
         return Ok(InstallStepOutput {
           success: true,
           msg: None,
@@ -328,13 +555,27 @@ pub async fn extract_iso_for_mod_install(
 
 #[tauri::command]
 pub async fn decompile_for_mod_install(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
   app_handle: tauri::AppHandle,
   game_name: String,
   mod_name: String,
   source_name: String,
+  version_name: Option<String>,
 ) -> Result<InstallStepOutput, CommandError> {
+  // the version being installed isn't recorded in the config yet, otherwise fall back to the
+  // settings of the installed version
+  let version_settings = match &version_name {
+    Some(version_name) => cache
+      .lock()
+      .await
+      .find_mod_version(&source_name, &mod_name, version_name)
+      .map(|version| version.settings.clone()),
+    None => None,
+  };
   let config_lock = config.lock().await;
+  let settings = version_settings
+    .unwrap_or_else(|| config_lock.mod_settings(&game_name, &source_name, &mod_name));
   let install_path = match &config_lock.installation_dir {
     None => {
       return Err(CommandError::GameFeatures(
@@ -360,21 +601,19 @@ pub async fn decompile_for_mod_install(
     }
   };
 
-  let iso_dir = install_path
-    .join("active")
-    .join(&game_name)
-    .join("data")
-    .join("iso_data")
-    .join(&game_name)
-    .to_path_buf();
+  let iso_dir = get_mod_iso_dir(install_path, &game_name, &mod_name, &source_name);
 
-  let args = vec![
+  let mut args = vec![
     iso_dir.clone().to_string_lossy().into_owned(),
     "--folder".to_string(),
     "--decompile".to_string(),
     "--game".to_string(),
     game_name.clone(),
   ];
+  if !settings.decomp_config_override.trim().is_empty() {
+    args.push("--decomp-config-override".to_string());
+    args.push(settings.decomp_config_override.clone());
+  }
 
   log::info!("Running extractor with args: {:?}", args);
 
@@ -390,8 +629,14 @@ pub async fn decompile_for_mod_install(
   }
   let mut child = command.spawn()?;
 
-  let mut log_file =
-    create_log_file(&app_handle, format!("extractor-{game_name}.log"), false).await?;
+  // the first step of an install that writes this log, so it starts a new one
+  let mut log_file = create_log_file(
+    &app_handle,
+    format!("extractor-{game_name}.log"),
+    false,
+    config_lock.log_retention_count,
+  )
+  .await?;
 
   let process_status = watch_process(&mut log_file, &mut child, &app_handle).await?;
 
@@ -459,13 +704,7 @@ pub async fn compile_for_mod_install(
     }
   };
 
-  let iso_dir = install_path
-    .join("active")
-    .join(&game_name)
-    .join("data")
-    .join("iso_data")
-    .join(&game_name)
-    .to_path_buf();
+  let iso_dir = get_mod_iso_dir(install_path, &game_name, &mod_name, &source_name);
 
   let args = vec![
     iso_dir.clone().to_string_lossy().into_owned(),
@@ -489,8 +728,14 @@ pub async fn compile_for_mod_install(
   }
   let mut child = command.spawn()?;
 
-  let mut log_file =
-    create_log_file(&app_handle, format!("extractor-{game_name}.log"), false).await?;
+  // the decompile step started this install's log, keep adding to it
+  let mut log_file = create_log_file(
+    &app_handle,
+    format!("extractor-{game_name}.log"),
+    true,
+    config_lock.log_retention_count,
+  )
+  .await?;
 
   let process_status = watch_process(&mut log_file, &mut child, &app_handle).await?;
   log_file.flush().await?;
@@ -524,12 +769,26 @@ pub async fn compile_for_mod_install(
 
 #[tauri::command]
 pub async fn save_mod_install_info(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
   game_name: String,
   mod_name: String,
   source_name: String,
   version_name: String,
 ) -> Result<InstallStepOutput, CommandError> {
+  // remember what the installed version depends on, so its dependencies can't be uninstalled, and
+  // its settings so they still apply when the mod source isn't available.  When the version isn't
+  // in the cache whatever was recorded before is the best we know, so it is left alone.
+  let cached_version_info = cache
+    .lock()
+    .await
+    .find_mod_version(&source_name, &mod_name, &version_name)
+    .map(|version| {
+      (
+        with_sources(&source_name, &version.dependencies),
+        version.settings.clone(),
+      )
+    });
   let mut config_lock = config.lock().await;
   log::info!(
     "Saving mod install info {}, {}, {}, {}",
@@ -541,27 +800,676 @@ pub async fn save_mod_install_info(
   config_lock
     .update_mods_setting_value(
       "add_mod",
-      game_name,
-      Some(source_name),
-      Some(version_name),
-      Some(mod_name),
+      game_name.clone(),
+      Some(source_name.clone()),
+      Some(version_name.clone()),
+      Some(mod_name.clone()),
       None,
     )
     .map_err(|err| {
       log::error!("Unable to remove mod source: {:?}", err);
       CommandError::Configuration("Unable to remove mod source".to_owned())
     })?;
+  match cached_version_info {
+    Some((dependencies, settings)) => {
+      config_lock
+        .set_mod_dependencies(&game_name, &source_name, &mod_name, dependencies)
+        .map_err(|err| {
+          log::error!("Unable to save mod dependencies: {:?}", err);
+          CommandError::Configuration("Unable to save mod dependencies".to_owned())
+        })?;
+      config_lock
+        .set_mod_settings(&game_name, &source_name, &mod_name, settings)
+        .map_err(|err| {
+          log::error!("Unable to save mod settings: {:?}", err);
+          CommandError::Configuration("Unable to save mod settings".to_owned())
+        })?;
+    }
+    None => log::warn!(
+      "Version {} of {} isn't in the mod source cache, keeping its recorded dependencies and settings",
+      version_name,
+      mod_name
+    ),
+  }
+  // the install succeeded, so only the versions kept for switching back to are still needed
+  if let Some(install_path) = &config_lock.installation_dir {
+    let mod_dir = Path::new(install_path)
+      .join("features")
+      .join(&game_name)
+      .join("mods")
+      .join(&source_name)
+      .join(&mod_name);
+    if let Err(err) = prune_previous_versions(
+      &mod_dir,
+      &version_name,
+      config_lock.mod_version_retention_count,
+    ) {
+      log::warn!("Unable to delete previous versions of the mod: {}", err);
+    }
+  }
   Ok(InstallStepOutput {
     success: true,
     msg: None,
   })
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModUpdateStatus {
+  pub source_name: String,
+  pub mod_name: String,
+  pub installed_version: String,
+  // newest version in the cached mod sources that supports the game on this platform
+  pub latest_version: Option<String>,
+  pub update_available: bool,
+}
+
+// The highest compatible version in the cached mod sources, versions that don't parse are ordered
+// by when they were published
+fn latest_compatible_version(
+  cache: &LauncherCache,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+) -> Option<ModVersion> {
+  let mod_info = cache.find_mod(source_name, mod_name)?;
+  let mut versions = mod_info
+    .compatible_versions(game_name, std::env::consts::OS)
+    .into_iter();
+  let first = versions.next()?;
+  let latest = versions.fold(first, |latest, version| {
+    if is_newer_version(mod_info, version, &latest.version) {
+      version
+    } else {
+      latest
+    }
+  });
+  Some(latest.clone())
+}
+
+// The latest compatible version when it is strictly newer than the installed one, an older version
+// that was published later (a re-upload or a backport) isn't an update
+fn available_update(
+  cache: &LauncherCache,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+  installed_version: &str,
+) -> Option<ModVersion> {
+  let mod_info = cache.find_mod(source_name, mod_name)?;
+  latest_compatible_version(cache, game_name, source_name, mod_name)
+    .filter(|latest| is_newer_version(mod_info, latest, installed_version))
+}
+
+#[tauri::command]
+pub async fn check_for_mod_updates(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+) -> Result<Vec<ModUpdateStatus>, CommandError> {
+  let cache_lock = cache.lock().await;
+  let config_lock = config.lock().await;
+  let game_config = config_lock
+    .game_config(&game_name)
+    .ok_or_else(|| CommandError::UnknownGame(format!("Unknown game {game_name}")))?;
+  let mut statuses = Vec::new();
+  for (source_name, mods) in &game_config.mods_installed_version {
+    for (mod_name, installed_version) in mods {
+      let latest = latest_compatible_version(&cache_lock, &game_name, source_name, mod_name);
+      statuses.push(ModUpdateStatus {
+        source_name: source_name.clone(),
+        mod_name: mod_name.clone(),
+        installed_version: installed_version.clone(),
+        update_available: available_update(
+          &cache_lock,
+          &game_name,
+          source_name,
+          mod_name,
+          installed_version,
+        )
+        .is_some(),
+        latest_version: latest.map(|latest| latest.version),
+      });
+    }
+  }
+  statuses.sort_by(|a, b| (&a.source_name, &a.mod_name).cmp(&(&b.source_name, &b.mod_name)));
+  Ok(statuses)
+}
+
+fn ensure_mod_not_running(
+  registry: &ProcessRegistry,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+) -> Result<(), CommandError> {
+  match registry.find(game_name, Some((source_name, mod_name))) {
+    Some(running) => Err(CommandError::GameManagement(format!(
+      "{} is running (pid {}), close it first",
+      mod_name, running.pid
+    ))),
+    None => Ok(()),
+  }
+}
+
+async fn plan_mod_install(
+  cache: &tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: &tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: &str,
+  mod_name: &str,
+  source_name: &str,
+  version: Option<&str>,
+) -> Result<Vec<PlannedModInstall>, CommandError> {
+  let cache_lock = cache.lock().await;
+  let config_lock = config.lock().await;
+  let installed = config_lock
+    .game_config(game_name)
+    .map(|game_config| game_config.mods_installed_version.clone())
+    .unwrap_or_default();
+  resolve_install_plan(
+    &cache_lock,
+    &installed,
+    game_name,
+    source_name,
+    mod_name,
+    version,
+  )
+  .map_err(|err| {
+    log::error!("Unable to resolve mod dependencies: {}", err);
+    CommandError::GameFeatures(format!("Unable to resolve mod dependencies: {err}"))
+  })
+}
+
+// Runs every install step for a single planned mod, stopping at the first step that fails and
+// restoring the version that was installed before
+async fn install_planned_mod(
+  cache: &tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: &tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: &tauri::AppHandle,
+  game_name: &str,
+  planned: &PlannedModInstall,
+  path_to_iso: &Option<String>,
+) -> Result<InstallStepOutput, CommandError> {
+  log::info!(
+    "Installing {} {} from {}",
+    planned.mod_name,
+    planned.version,
+    planned.source_name
+  );
+  if planned.download_url.is_empty() {
+    return Ok(InstallStepOutput {
+      success: false,
+      msg: Some(format!(
+        "Version {} of {} has no download for this platform",
+        planned.version, planned.mod_name
+      )),
+    });
+  }
+  let output =
+    run_planned_mod_install_steps(cache, config, app_handle, game_name, planned, path_to_iso).await;
+  if !matches!(output, Ok(InstallStepOutput { success: true, .. })) {
+    let config_lock = config.lock().await;
+    if let Err(err) = rollback_installed_mod(
+      &config_lock,
+      game_name,
+      &planned.source_name,
+      &planned.mod_name,
+    ) {
+      log::error!("Unable to roll back {}: {:?}", planned.mod_name, err);
+    }
+  }
+  output
+}
+
+async fn run_planned_mod_install_steps(
+  cache: &tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: &tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: &tauri::AppHandle,
+  game_name: &str,
+  planned: &PlannedModInstall,
+  path_to_iso: &Option<String>,
+) -> Result<InstallStepOutput, CommandError> {
+  let output = download_and_extract_new_mod(
+    config.clone(),
+    app_handle.clone(),
+    game_name.to_owned(),
+    planned.download_url.clone(),
+    planned.mod_name.clone(),
+    planned.source_name.clone(),
+  )
+  .await?;
+  if !output.success {
+    return Ok(output);
+  }
+  if !base_game_iso_exists(config.clone(), game_name.to_owned()).await? {
+    let output = match path_to_iso {
+      Some(path_to_iso) => {
+        extract_iso_for_mod_install(
+          config.clone(),
+          app_handle.clone(),
+          game_name.to_owned(),
+          planned.mod_name.clone(),
+          planned.source_name.clone(),
+          path_to_iso.clone(),
+        )
+        .await?
+      }
+      None => InstallStepOutput {
+        success: false,
+        msg: Some(format!(
+          "The {game_name} ISO has to be provided to install {}",
+          planned.mod_name
+        )),
+      },
+    };
+    if !output.success {
+      return Ok(output);
+    }
+  }
+  let output = decompile_for_mod_install(
+    cache.clone(),
+    config.clone(),
+    app_handle.clone(),
+    game_name.to_owned(),
+    planned.mod_name.clone(),
+    planned.source_name.clone(),
+    Some(planned.version.clone()),
+  )
+  .await?;
+  if !output.success {
+    return Ok(output);
+  }
+  let output = compile_for_mod_install(
+    config.clone(),
+    app_handle.clone(),
+    game_name.to_owned(),
+    planned.mod_name.clone(),
+    planned.source_name.clone(),
+  )
+  .await?;
+  if !output.success {
+    return Ok(output);
+  }
+  save_mod_install_info(
+    cache.clone(),
+    config.clone(),
+    game_name.to_owned(),
+    planned.mod_name.clone(),
+    planned.source_name.clone(),
+    planned.version.clone(),
+  )
+  .await
+}
+
+// Installs every mod in the plan in order, stopping at the first one that fails
+pub async fn install_plan(
+  cache: &tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: &tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: &tauri::AppHandle,
+  game_name: &str,
+  plan: &[PlannedModInstall],
+  path_to_iso: &Option<String>,
+) -> Result<InstallStepOutput, CommandError> {
+  {
+    let registry = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+    let registry = registry.lock().await;
+    for planned in plan {
+      ensure_mod_not_running(
+        &registry,
+        game_name,
+        &planned.source_name,
+        &planned.mod_name,
+      )?;
+    }
+  }
+  for planned in plan {
+    let output =
+      install_planned_mod(cache, config, app_handle, game_name, planned, path_to_iso).await?;
+    if !output.success {
+      return Ok(output);
+    }
+  }
+  Ok(InstallStepOutput {
+    success: true,
+    msg: None,
+  })
+}
+
+// The mods that would be installed, dependencies first, for the given mod to work
+#[tauri::command]
+pub async fn resolve_mod_install_plan(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+  version: Option<String>,
+) -> Result<Vec<PlannedModInstall>, CommandError> {
+  plan_mod_install(
+    &cache,
+    &config,
+    &game_name,
+    &mod_name,
+    &source_name,
+    version.as_deref(),
+  )
+  .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn install_mod_with_dependencies(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+  version: Option<String>,
+  path_to_iso: Option<String>,
+) -> Result<InstallStepOutput, CommandError> {
+  let plan = plan_mod_install(
+    &cache,
+    &config,
+    &game_name,
+    &mod_name,
+    &source_name,
+    version.as_deref(),
+  )
+  .await?;
+  install_plan(
+    &cache,
+    &config,
+    &app_handle,
+    &game_name,
+    &plan,
+    &path_to_iso,
+  )
+  .await
+}
+
+// Restores the version of the mod that was installed before an install that failed part way
+// through, returns whether there was a version to restore
+#[tauri::command]
+pub async fn rollback_mod_install(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+) -> Result<bool, CommandError> {
+  let config_lock = config.lock().await;
+  rollback_installed_mod(&config_lock, &game_name, &source_name, &mod_name)
+}
+
+// The versions of the mod that were installed before and can be switched back to, most recent first
+#[tauri::command]
+pub async fn get_previous_mod_versions(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+) -> Result<Vec<String>, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => return Ok(Vec::new()),
+    Some(path) => Path::new(path),
+  };
+  let mod_dir = install_path
+    .join("features")
+    .join(&game_name)
+    .join("mods")
+    .join(&source_name)
+    .join(&mod_name);
+  previous_versions(&mod_dir).map_err(|err| {
+    log::error!("Unable to list previous versions of the mod: {}", err);
+    CommandError::GameFeatures(format!(
+      "Unable to list previous versions of the mod: {}",
+      err
+    ))
+  })
+}
+
+// Swaps the installed version of the mod for one that was installed before, the installed version
+// is kept in its place so it can be switched back to as well
+#[tauri::command]
+pub async fn switch_mod_version(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+  version_name: String,
+) -> Result<InstallStepOutput, CommandError> {
+  {
+    let registry = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+    let registry = registry.lock().await;
+    ensure_mod_not_running(&registry, &game_name, &source_name, &mod_name)?;
+  }
+  {
+    let config_lock = config.lock().await;
+    let install_path = match &config_lock.installation_dir {
+      None => {
+        return Err(CommandError::GameFeatures(
+          "No installation directory set, can't switch mod version".to_string(),
+        ))
+      }
+      Some(path) => Path::new(path),
+    };
+    let mod_dir = install_path
+      .join("features")
+      .join(&game_name)
+      .join("mods")
+      .join(&source_name)
+      .join(&mod_name);
+    let installed_version = config_lock
+      .installed_mod_version(&game_name, &source_name, &mod_name)
+      .ok_or_else(|| CommandError::GameFeatures(format!("{mod_name} isn't installed")))?;
+    if !has_previous_version(&mod_dir, &version_name) {
+      return Ok(InstallStepOutput {
+        success: false,
+        msg: Some(format!(
+          "Version {version_name} of {mod_name} is no longer available"
+        )),
+      });
+    }
+    log::info!(
+      "Switching {} from version {} to {}",
+      mod_name,
+      installed_version,
+      version_name
+    );
+    // both steps move back whatever they moved when they fail, so a failed keep leaves the
+    // installed version where it is and a failed restore leaves nothing installed
+    keep_installed_version(&mod_dir, &installed_version).map_err(|err| {
+      log::error!("Unable to keep the installed mod version: {}", err);
+      CommandError::GameFeatures(format!("Unable to switch mod version: {}", err))
+    })?;
+    if let Err(err) = restore_previous_version(&mod_dir, &version_name) {
+      log::error!("Unable to switch mod version: {}", err);
+      if let Err(err) = restore_previous_version(&mod_dir, &installed_version) {
+        log::error!("Unable to restore the installed version: {}", err);
+      }
+      return Err(CommandError::GameFeatures(format!(
+        "Unable to switch mod version: {}",
+        err
+      )));
+    }
+  }
+  save_mod_install_info(
+    cache,
+    config,
+    game_name,
+    mod_name,
+    source_name,
+    version_name,
+  )
+  .await
+}
+
+// Installs the newest compatible version of the mod over the current one, along with any new
+// dependencies.  The mod's settings and saves live in `_settings` beside the mod's folder, so they
+// survive the folder being replaced.
+#[tauri::command]
+pub async fn update_mod(
+  cache: tauri::State<'_, tokio::sync::Mutex<LauncherCache>>,
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+) -> Result<InstallStepOutput, CommandError> {
+  let latest_version = {
+    let cache_lock = cache.lock().await;
+    let config_lock = config.lock().await;
+    match config_lock.installed_mod_version(&game_name, &source_name, &mod_name) {
+      Some(installed_version) => {
+        match available_update(
+          &cache_lock,
+          &game_name,
+          &source_name,
+          &mod_name,
+          &installed_version,
+        ) {
+          Some(latest) => Some(latest.version),
+          None => {
+            log::info!(
+              "{} from {} is already at the latest version {}",
+              mod_name,
+              source_name,
+              installed_version
+            );
+            return Ok(InstallStepOutput {
+              success: true,
+              msg: None,
+            });
+          }
+        }
+      }
+      None => latest_compatible_version(&cache_lock, &game_name, &source_name, &mod_name)
+        .map(|latest| latest.version),
+    }
+  };
+  let plan = plan_mod_install(
+    &cache,
+    &config,
+    &game_name,
+    &mod_name,
+    &source_name,
+    latest_version.as_deref(),
+  )
+  .await?;
+  if let Some(latest) = plan.last() {
+    log::info!(
+      "Updating {} from {} to version {}",
+      mod_name,
+      source_name,
+      latest.version
+    );
+  }
+  install_plan(&cache, &config, &app_handle, &game_name, &plan, &None).await
+}
+
+// Where the mod keeps its settings and saves
+fn get_mod_config_dir(
+  app_handle: &tauri::AppHandle,
+  config: &LauncherConfig,
+  install_path: &Path,
+  game_name: &str,
+  mod_name: &str,
+  source_name: &str,
+) -> Result<PathBuf, CommandError> {
+  let config_dir = install_path
+    .join("features")
+    .join(game_name)
+    .join("mods")
+    .join(source_name)
+    .join("_settings")
+    .join(mod_name);
+  let share_vanilla_saves = config
+    .mod_settings(game_name, source_name, mod_name)
+    .share_vanilla_saves;
+  link_vanilla_saves(app_handle, &config_dir, game_name, share_vanilla_saves).map_err(|err| {
+    log::error!("Unable to set up the mod's saves: {}", err);
+    CommandError::GameManagement(format!("Unable to set up the mod's saves: {err}"))
+  })?;
+  Ok(config_dir)
+}
+
+// Mods that share the vanilla game's saves get their saves folder linked to the vanilla game's,
+// their settings stay their own.  Saves a mod made before it started sharing are kept beside the
+// link as `saves-unshared`, and are put back if it stops.
+fn link_vanilla_saves(
+  app_handle: &tauri::AppHandle,
+  mod_config_dir: &Path,
+  game_name: &str,
+  share_vanilla_saves: bool,
+) -> Result<(), std::io::Error> {
+  let game_config_dir = mod_config_dir.join(game_name);
+  let saves_dir = game_config_dir.join("saves");
+  let unshared_saves_dir = game_config_dir.join("saves-unshared");
+  if !share_vanilla_saves {
+    if is_link(&saves_dir) {
+      log::info!(
+        "No longer sharing vanilla saves with {}",
+        mod_config_dir.display()
+      );
+      remove_dir_link(&saves_dir)?;
+      if unshared_saves_dir.exists() {
+        std::fs::rename(&unshared_saves_dir, &saves_dir)?;
+      }
+    }
+    return Ok(());
+  }
+
+  let vanilla_saves_dir = app_handle
+    .path()
+    .config_dir()
+    .map_err(|_| {
+      std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "Could not determine game config directory",
+      )
+    })?
+    .join("OpenGOAL")
+    .join(game_name)
+    .join("saves");
+  create_dir(&vanilla_saves_dir)?;
+  if is_link(&saves_dir) {
+    if std::fs::read_link(&saves_dir).is_ok_and(|target| target == vanilla_saves_dir) {
+      return Ok(());
+    }
+    remove_dir_link(&saves_dir)?;
+  } else if saves_dir.exists() {
+    if std::fs::read_dir(&saves_dir)?.next().is_none() {
+      std::fs::remove_dir(&saves_dir)?;
+    } else if unshared_saves_dir.exists() {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!(
+          "{} has saves of its own and older ones in {}, move one of them to share the vanilla saves",
+          mod_config_dir.display(),
+          unshared_saves_dir.display()
+        ),
+      ));
+    } else {
+      std::fs::rename(&saves_dir, &unshared_saves_dir)?;
+    }
+  }
+  create_dir(&game_config_dir)?;
+  log::info!(
+    "Linking {} to the vanilla saves in {}",
+    saves_dir.display(),
+    vanilla_saves_dir.display()
+  );
+  link_dir(&vanilla_saves_dir, &saves_dir)
+}
+
 fn generate_launch_mod_args(
   game_name: String,
   in_debug: bool,
   config_dir: PathBuf,
   quote_project_path: bool,
+  launch_profile: &LaunchProfile,
 ) -> Result<Vec<String>, CommandError> {
   let config_dir_adjusted = if quote_project_path {
     format!("\"{}\"", config_dir.to_string_lossy().into_owned())
@@ -583,10 +1491,31 @@ fn generate_launch_mod_args(
     args.push("-debug".to_string());
   }
 
-  Ok(args)
+  Ok(launch_profile.apply_args(args))
+}
+
+// Mods use the executable that shipped with them unless the launch profile says otherwise
+fn get_mod_launch_exec_location(
+  install_path: &Path,
+  launch_profile: &LaunchProfile,
+  game_name: &str,
+  mod_name: &str,
+  source_name: &str,
+) -> Result<ExecutableLocation, CommandError> {
+  match &launch_profile.executable_path {
+    Some(executable_path) => custom_exec_location(executable_path),
+    None => get_mod_exec_location(
+      install_path.to_path_buf(),
+      "gk",
+      game_name,
+      mod_name,
+      source_name,
+    ),
+  }
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn launch_mod(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
   app_handle: tauri::AppHandle,
@@ -594,6 +1523,8 @@ pub async fn launch_mod(
   in_debug: bool,
   mod_name: String,
   source_name: String,
+  launch_profile: Option<String>,
+  allow_concurrent: Option<bool>,
 ) -> Result<(), CommandError> {
   let config_lock = config.lock().await;
   let install_path = match &config_lock.installation_dir {
@@ -604,43 +1535,110 @@ pub async fn launch_mod(
     }
     Some(path) => Path::new(path),
   };
-  let config_dir = install_path
-    .join("features")
-    .join(&game_name)
-    .join("mods")
-    .join(&source_name)
-    .join("_settings")
-    .join(&mod_name);
-  let exec_info = get_mod_exec_location(
-    install_path.to_path_buf(),
-    "gk",
+  // checked before the config dir is prepared, that re-links the saves a running instance uses
+  let registry_state = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+  let mut registry = registry_state.lock().await;
+  let mod_info = Some((source_name.as_str(), mod_name.as_str()));
+  if let Some(running) = registry.find(&game_name, mod_info) {
+    if !allow_concurrent.unwrap_or(false) {
+      return Err(CommandError::GameManagement(format!(
+        "{} is already running (pid {})",
+        mod_name, running.pid
+      )));
+    }
+  }
+
+  let profile = resolve_launch_profile(
+    &config_lock,
+    &game_name,
+    Some((&source_name, &mod_name)),
+    &launch_profile,
+  )?;
+  let config_dir = get_mod_config_dir(
+    &app_handle,
+    &config_lock,
+    install_path,
     &game_name,
     &mod_name,
     &source_name,
   )?;
-  let args = generate_launch_mod_args(game_name.clone(), in_debug, config_dir, false)?;
+  let exec_info =
+    get_mod_launch_exec_location(install_path, &profile, &game_name, &mod_name, &source_name)?;
+  let args = generate_launch_mod_args(game_name.clone(), in_debug, config_dir, false, &profile)?;
+  let (program, args) = profile.command_line(&exec_info.executable_path, args);
 
-  log::info!("Launching gk args: {:?}", args);
+  log::info!("Launching {:?} with args: {:?}", program, args);
 
+  let mod_version = config_lock
+    .game_config(&game_name)
+    .and_then(|game_config| game_config.mods_installed_version.get(&source_name))
+    .and_then(|mods| mods.get(&mod_name))
+    .cloned();
+
+  let log_name = registry.free_log_name(&format!("game-{game_name}-{mod_name}.log"));
+  let log_path = get_log_file_path(&app_handle, &log_name);
   let log_file = create_std_log_file(
     &app_handle,
-    format!("game-{game_name}-{mod_name}.log"),
+    log_name,
     false,
+    config_lock.log_retention_count,
   )?;
 
-  // TODO - log rotation here would be nice too
-  let mut command = Command::new(exec_info.executable_path);
+  let mut command = std::process::Command::new(program);
   command
     .args(args)
-    .stdout(log_file.try_clone().unwrap())
-    .stderr(log_file)
+    .envs(&profile.env_vars)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
     .current_dir(exec_info.executable_dir);
   #[cfg(windows)]
   {
     command.creation_flags(0x08000000);
   }
   // Start the process here so if there is an error, we can return immediately
-  let _child = command.spawn()?;
+  let mut child = command.spawn()?;
+  pipe_output_to_log(&mut child, log_file);
+  let pid = child.id();
+  registry.register(
+    &app_handle,
+    child,
+    RunningGame::new(pid, &game_name, mod_info, mod_version, log_path),
+  );
+  drop(registry);
+  tokio::spawn(async move {
+    let start_time = Instant::now();
+    if let Err(err) = wait_for_game_exit(&app_handle, pid).await {
+      log::error!("Error occured when waiting for mod to exit: {}", err);
+      return;
+    }
+    if let Err(err) =
+      track_mod_playtime(&app_handle, start_time, game_name, source_name, mod_name).await
+    {
+      log::error!("Error occured when tracking mod playtime: {}", err);
+    }
+  });
+  Ok(())
+}
+
+async fn track_mod_playtime(
+  app_handle: &tauri::AppHandle,
+  start_time: Instant,
+  game_name: String,
+  source_name: String,
+  mod_name: String,
+) -> Result<(), CommandError> {
+  let config = app_handle.state::<tokio::sync::Mutex<LauncherConfig>>();
+  let mut config_lock = config.lock().await;
+
+  let elapsed_time = start_time.elapsed().as_secs();
+  log::info!("elapsed time for {}: {}", mod_name, elapsed_time);
+
+  config_lock
+    .add_mod_playtime(&game_name, &source_name, &mod_name, elapsed_time)
+    .map_err(|_| CommandError::Configuration("Unable to persist mod time played".to_owned()))?;
+
+  // same event as the vanilla game, so the frontend can refresh the playtime on screen
+  app_handle.emit("playtimeUpdated", ())?;
   Ok(())
 }
 
@@ -656,14 +1654,17 @@ pub async fn get_local_mod_thumbnail_base64(
     Some(path) => Path::new(path),
   };
 
-  let cover_path = install_path
+  let mod_dir = install_path
     .join("features")
     .join(game_name)
     .join("mods")
     .join("_local")
-    .join(mod_name)
-    .join("thumbnail.png");
-  if cover_path.exists() {
+    .join(mod_name);
+  let manifest = read_installed_manifest(&mod_dir);
+  let art = manifest
+    .as_ref()
+    .and_then(|manifest| manifest.thumbnail.as_ref());
+  if let Some(cover_path) = mod_art_path(&mod_dir, art, THUMBNAIL_FILE_NAME) {
     return Ok(to_image_base64(cover_path.to_string_lossy().as_ref()));
   }
   Ok("".to_string())
@@ -681,19 +1682,156 @@ pub async fn get_local_mod_cover_base64(
     Some(path) => Path::new(path),
   };
 
-  let cover_path = install_path
+  let mod_dir = install_path
     .join("features")
     .join(game_name)
     .join("mods")
     .join("_local")
-    .join(mod_name)
-    .join("cover.png");
-  if cover_path.exists() {
+    .join(mod_name);
+  let manifest = read_installed_manifest(&mod_dir);
+  let art = manifest
+    .as_ref()
+    .and_then(|manifest| manifest.cover.as_ref());
+  if let Some(cover_path) = mod_art_path(&mod_dir, art, COVER_FILE_NAME) {
     return Ok(to_image_base64(cover_path.to_string_lossy().as_ref()));
   }
   Ok("".to_string())
 }
 
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModInfo {
+  pub mod_name: String,
+  pub display_name: String,
+  pub installed_version: Option<String>,
+  // only mods imported from an archive with a manifest have one
+  pub manifest: Option<ModManifest>,
+  pub cover_path: Option<String>,
+  pub thumbnail_path: Option<String>,
+}
+
+// The mods in the `_local` source along with whatever their manifests say about them
+#[tauri::command]
+pub async fn list_local_mods(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+) -> Result<Vec<LocalModInfo>, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => return Ok(Vec::new()),
+    Some(path) => Path::new(path),
+  };
+  let local_dir = install_path
+    .join("features")
+    .join(&game_name)
+    .join("mods")
+    .join("_local");
+  if !local_dir.is_dir() {
+    return Ok(Vec::new());
+  }
+  let mut local_mods = Vec::new();
+  for entry in std::fs::read_dir(&local_dir)? {
+    let mod_dir = entry?.path();
+    let mod_name = match mod_dir.file_name() {
+      Some(name) => name.to_string_lossy().into_owned(),
+      None => continue,
+    };
+    // `_settings` holds the settings and saves of every local mod
+    if !mod_dir.is_dir() || mod_name.starts_with(['.', '_']) {
+      continue;
+    }
+    let manifest = read_installed_manifest(&mod_dir);
+    let art_path = |art: Option<&String>, default: &str| {
+      mod_art_path(&mod_dir, art, default).map(|path| path.to_string_lossy().into_owned())
+    };
+    local_mods.push(LocalModInfo {
+      display_name: manifest
+        .as_ref()
+        .map(|manifest| manifest.display_name.clone())
+        .filter(|display_name| !display_name.trim().is_empty())
+        .unwrap_or_else(|| mod_name.clone()),
+      installed_version: config_lock.installed_mod_version(&game_name, "_local", &mod_name),
+      cover_path: art_path(
+        manifest
+          .as_ref()
+          .and_then(|manifest| manifest.cover.as_ref()),
+        COVER_FILE_NAME,
+      ),
+      thumbnail_path: art_path(
+        manifest
+          .as_ref()
+          .and_then(|manifest| manifest.thumbnail.as_ref()),
+        THUMBNAIL_FILE_NAME,
+      ),
+      manifest,
+      mod_name,
+    });
+  }
+  local_mods.sort_by(|a, b| a.mod_name.cmp(&b.mod_name));
+  Ok(local_mods)
+}
+
+// Packages a mod from the `_local` source so it can be published in a mod source
+#[tauri::command]
+pub async fn package_local_mod(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  mod_name: String,
+  options: ModPackageOptions,
+) -> Result<ModPackage, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => {
+      return Err(CommandError::GameFeatures(
+        "No installation directory set, can't package mod".to_string(),
+      ))
+    }
+    Some(path) => Path::new(path),
+  };
+  let mod_dir = install_path
+    .join("features")
+    .join(&game_name)
+    .join("mods")
+    .join("_local")
+    .join(&mod_name);
+  let default_output_dir = install_path
+    .join("features")
+    .join(&game_name)
+    .join("mod-packages")
+    .join(&mod_name);
+  package_mod(
+    &mod_dir,
+    &default_output_dir,
+    &game_name,
+    &mod_name,
+    &options,
+  )
+  .map_err(|err| {
+    log::error!("Unable to package mod {}: {}", mod_name, err);
+    CommandError::GameFeatures(format!("Unable to package mod: {}", err))
+  })
+}
+
+// Writes a mod source for the mods in `source_dir`, which will be hosted at `base_url`.  Written
+// to `mod-source.json` in `source_dir` unless `output_path` is given.
+#[tauri::command]
+pub async fn generate_mod_source(
+  source_dir: String,
+  source_name: String,
+  base_url: String,
+  output_path: Option<String>,
+) -> Result<GeneratedModSource, CommandError> {
+  let source_dir = PathBuf::from(source_dir);
+  let output_path = output_path
+    .map(PathBuf::from)
+    .unwrap_or_else(|| source_dir.join("mod-source.json"));
+  mod_source_generator::generate_mod_source(&source_dir, &source_name, &base_url, &output_path)
+    .map_err(|err| {
+      log::error!("Unable to generate mod source: {}", err);
+      CommandError::GameFeatures(format!("Unable to generate mod source: {}", err))
+    })
+}
+
 #[tauri::command]
 pub async fn uninstall_mod(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
@@ -702,6 +1840,16 @@ pub async fn uninstall_mod(
   source_name: String,
 ) -> Result<(), CommandError> {
   let mut config_lock = config.lock().await;
+  let required_by = config_lock
+    .game_config(&game_name)
+    .map(|game_config| dependents(&game_config.mods_dependencies, &source_name, &mod_name))
+    .unwrap_or_default();
+  if !required_by.is_empty() {
+    return Err(CommandError::GameFeatures(format!(
+      "Can't uninstall {mod_name}, it is required by {}",
+      required_by.join(", ")
+    )));
+  }
   let install_path = match &config_lock.installation_dir {
     None => {
       return Err(CommandError::GameFeatures(
@@ -719,6 +1867,11 @@ pub async fn uninstall_mod(
   if mod_dir.exists() {
     std::fs::remove_dir_all(mod_dir)?;
   }
+  if let Err(err) =
+    IsoLibrary::load(install_path).release(&game_name, &mod_reference(&source_name, &mod_name))
+  {
+    log::error!("Unable to release mod's ISO reference: {}", err);
+  }
   config_lock
     .update_mods_setting_value(
       "uninstall_mod",
@@ -775,9 +1928,11 @@ pub async fn reset_mod_settings(
 #[tauri::command]
 pub async fn get_launch_mod_string(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
   game_name: String,
   mod_name: String,
   source_name: String,
+  launch_profile: Option<String>,
 ) -> Result<String, CommandError> {
   let config_lock = config.lock().await;
   let install_path = match &config_lock.installation_dir {
@@ -788,25 +1943,29 @@ pub async fn get_launch_mod_string(
     }
     Some(path) => Path::new(path),
   };
-  let exec_info = get_mod_exec_location(
-    install_path.to_path_buf(),
-    "gk",
+  let profile = resolve_launch_profile(
+    &config_lock,
+    &game_name,
+    Some((&source_name, &mod_name)),
+    &launch_profile,
+  )?;
+  let exec_info =
+    get_mod_launch_exec_location(install_path, &profile, &game_name, &mod_name, &source_name)?;
+  let config_dir = get_mod_config_dir(
+    &app_handle,
+    &config_lock,
+    install_path,
     &game_name,
     &mod_name,
     &source_name,
   )?;
-  let config_dir = install_path
-    .join("features")
-    .join(&game_name)
-    .join("mods")
-    .join(&source_name)
-    .join("_settings")
-    .join(&mod_name);
-  let args = generate_launch_mod_args(game_name, false, config_dir, true)?;
+  let args = generate_launch_mod_args(game_name, false, config_dir, true, &profile)?;
+  let (program, args) = profile.command_line(&exec_info.executable_path, args);
 
   Ok(format!(
-    "{} {}",
-    exec_info.executable_path.display(),
+    "{}{} {}",
+    profile.env_prefix(),
+    program.display(),
     args.join(" ")
   ))
 }
@@ -834,13 +1993,7 @@ pub async fn open_repl_for_mod(
     }
     Some(path) => Path::new(path),
   };
-  let iso_dir = install_path
-    .join("active")
-    .join(&game_name)
-    .join("data")
-    .join("iso_data")
-    .join(&game_name)
-    .to_path_buf();
+  let iso_dir = get_mod_iso_dir(install_path, &game_name, &mod_name, &source_name);
   let exec_info = get_mod_exec_location(
     install_path.to_path_buf(),
     "goalc",
@@ -867,10 +2020,27 @@ pub async fn open_repl_for_mod(
   }
   #[cfg(target_os = "linux")]
   {
-    command = std::process::Command::new("xdg-terminal-exec");
-    command
-      .args(["./goalc", "--iso-path", &iso_dir.to_string_lossy()])
-      .current_dir(exec_info.executable_dir);
+    let terminal = match resolve_terminal(config_lock.preferred_terminal.as_deref()) {
+      Some(terminal) => terminal,
+      None => {
+        let _ = app_handle.emit(
+          "toast_msg",
+          ToastPayload {
+            toast: "No terminal emulator found, set one in the launcher's settings".to_owned(),
+            level: "error".to_string(),
+          },
+        );
+        return Err(CommandError::BinaryExecution(
+          "Unable to find a terminal to launch the REPL in".to_owned(),
+        ));
+      }
+    };
+    log::info!("Opening REPL in {}", terminal.path.display());
+    command = terminal.command(
+      &exec_info.executable_path,
+      &["--iso-path", &iso_dir.to_string_lossy()],
+    );
+    command.current_dir(exec_info.executable_dir);
   }
   #[cfg(target_os = "macos")]
   {
@@ -904,3 +2074,43 @@ pub async fn open_repl_for_mod(
     }
   }
 }
+
+#[tauri::command]
+pub async fn start_repl_for_mod(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  mod_name: String,
+  source_name: String,
+  port: Option<u16>,
+) -> Result<ReplSessionInfo, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => {
+      return Err(CommandError::GameFeatures(
+        "No installation directory set, can't start REPL for mod".to_string(),
+      ))
+    }
+    Some(path) => Path::new(path).to_path_buf(),
+  };
+  drop(config_lock);
+  let iso_dir = get_mod_iso_dir(&install_path, &game_name, &mod_name, &source_name);
+  let exec_info =
+    get_mod_exec_location(install_path, "goalc", &game_name, &mod_name, &source_name)?;
+  start_goalc(
+    &app_handle,
+    &exec_info.executable_dir,
+    vec![
+      "--iso-path".to_owned(),
+      iso_dir.to_string_lossy().into_owned(),
+    ],
+    &game_name,
+    Some((&source_name, &mod_name)),
+    port,
+  )
+  .await
+  .map_err(|err| {
+    log::error!("Unable to start REPL for mod: {}", err);
+    CommandError::Repl(format!("Unable to start REPL: {err}"))
+  })
+}
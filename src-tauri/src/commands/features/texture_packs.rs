@@ -10,8 +10,8 @@ use crate::{
   commands::CommandError,
   config::LauncherConfig,
   util::{
+    archive::{archive_contains_path, archive_name, emit_extraction_progress, extract_archive},
     file::{create_dir, delete_dir, overwrite_dir},
-    zip::{check_if_zip_contains_top_level_dir, extract_zip_file},
   },
 };
 
@@ -162,6 +162,7 @@ pub async fn list_extracted_texture_pack_info(
 #[tauri::command]
 pub async fn extract_new_texture_pack(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
   game_name: String,
   zip_path: String,
 ) -> Result<bool, CommandError> {
@@ -175,24 +176,21 @@ pub async fn extract_new_texture_pack(
     Some(path) => Path::new(path),
   };
 
-  // First, we'll check the zip file to make sure it has a `custom_assets/<game>/texture_replacements` folder before extracting
+  // First, we'll check the archive to make sure it has a `custom_assets/<game>/texture_replacements` folder before extracting
   let zip_path_buf = PathBuf::from(zip_path);
-  let texture_pack_name = match zip_path_buf.file_stem() {
-    Some(name) => name.to_string_lossy().to_string(),
+  let texture_pack_name = match archive_name(&zip_path_buf) {
+    Some(name) => name,
     None => {
       return Err(CommandError::GameFeatures(
-        "Unable to get texture pack name from zip file path".to_string(),
+        "Unable to get texture pack name from archive file path".to_string(),
       ));
     }
   };
   let expected_top_level_dir = format!("custom_assets/{}/texture_replacements", &game_name);
-  let valid_zip =
-    check_if_zip_contains_top_level_dir(&zip_path_buf, expected_top_level_dir.clone()).map_err(
-      |err| {
-        log::error!("Unable to read texture replacement zip file: {}", err);
-        CommandError::GameFeatures(format!("Unable to read texture replacement pack: {}", err))
-      },
-    )?;
+  let valid_zip = archive_contains_path(&zip_path_buf, &expected_top_level_dir).map_err(|err| {
+    log::error!("Unable to read texture replacement archive: {}", err);
+    CommandError::GameFeatures(format!("Unable to read texture replacement pack: {}", err))
+  })?;
   if !valid_zip {
     log::error!(
       "Invalid texture pack, no top-level `{}` folder in: {}",
@@ -201,7 +199,7 @@ pub async fn extract_new_texture_pack(
     );
     return Ok(false);
   }
-  // It's valid, let's extract it.  The name of the zip becomes the folder, if one already exists it will be deleted!
+  // It's valid, let's extract it.  The name of the archive becomes the folder, if one already exists it will be deleted!
   let destination_dir = &install_path
     .join("features")
     .join(game_name)
@@ -215,7 +213,13 @@ pub async fn extract_new_texture_pack(
       err
     ))
   })?;
-  extract_zip_file(&zip_path_buf, destination_dir, false).map_err(|err| {
+  extract_archive(
+    &zip_path_buf,
+    destination_dir,
+    false,
+    &mut emit_extraction_progress(&app_handle),
+  )
+  .map_err(|err| {
     log::error!("Unable to extract replacement pack: {}", err);
     CommandError::GameFeatures(format!("Unable to extract texture pack: {}", err))
   })?;
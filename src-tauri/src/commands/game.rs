@@ -9,11 +9,103 @@ use walkdir::WalkDir;
 
 use crate::{
   config::LauncherConfig,
+  crash_reports::{list_crash_reports, CrashReport},
+  iso_library::{vanilla_reference, IsoLibrary},
+  process_registry::{ProcessRegistry, RunningGame},
+  session_history::{
+    read_sessions, session_log_path, PlaySession, PlaySessionQuery, PlaySessionSummary,
+  },
   util::game_milestones::{get_jak1_milestones, GameTaskStatus, MilestoneCriteria},
 };
 
 use super::CommandError;
 
+#[tauri::command]
+pub async fn list_running_games(
+  registry: tauri::State<'_, tokio::sync::Mutex<ProcessRegistry>>,
+) -> Result<Vec<RunningGame>, CommandError> {
+  Ok(registry.lock().await.list())
+}
+
+#[tauri::command]
+pub async fn is_game_running(
+  registry: tauri::State<'_, tokio::sync::Mutex<ProcessRegistry>>,
+  game_name: String,
+  mod_name: Option<String>,
+  source_name: Option<String>,
+) -> Result<bool, CommandError> {
+  let mod_info = match (&source_name, &mod_name) {
+    (Some(source_name), Some(mod_name)) => Some((source_name.as_str(), mod_name.as_str())),
+    _ => None,
+  };
+  Ok(registry.lock().await.find(&game_name, mod_info).is_some())
+}
+
+#[tauri::command]
+pub async fn stop_game(
+  registry: tauri::State<'_, tokio::sync::Mutex<ProcessRegistry>>,
+  pid: u32,
+) -> Result<(), CommandError> {
+  registry.lock().await.stop(pid).map_err(|err| {
+    log::error!("Unable to stop game: {}", err);
+    CommandError::GameManagement(format!("Unable to stop game: {err}"))
+  })
+}
+
+#[tauri::command]
+pub async fn kill_game(
+  registry: tauri::State<'_, tokio::sync::Mutex<ProcessRegistry>>,
+  pid: u32,
+) -> Result<(), CommandError> {
+  registry.lock().await.kill(pid).map_err(|err| {
+    log::error!("Unable to kill game: {}", err);
+    CommandError::GameManagement(format!("Unable to kill game: {err}"))
+  })
+}
+
+fn query_play_sessions(
+  app_handle: &tauri::AppHandle,
+  query: &PlaySessionQuery,
+) -> Result<Vec<PlaySession>, CommandError> {
+  let log_path = session_log_path(app_handle).ok_or_else(|| {
+    CommandError::GameManagement("Unable to determine where play sessions are stored".to_owned())
+  })?;
+  let sessions = read_sessions(&log_path)
+    .and_then(|sessions| query.filter(sessions))
+    .map_err(|err| {
+      log::error!("Unable to read play sessions: {}", err);
+      CommandError::GameManagement(format!("Unable to read play sessions: {err}"))
+    })?;
+  Ok(sessions)
+}
+
+#[tauri::command]
+pub async fn get_play_sessions(
+  app_handle: tauri::AppHandle,
+  query: PlaySessionQuery,
+) -> Result<Vec<PlaySession>, CommandError> {
+  query_play_sessions(&app_handle, &query)
+}
+
+#[tauri::command]
+pub async fn get_play_session_summary(
+  app_handle: tauri::AppHandle,
+  query: PlaySessionQuery,
+) -> Result<PlaySessionSummary, CommandError> {
+  let sessions = query_play_sessions(&app_handle, &query)?;
+  Ok(PlaySessionSummary::from_sessions(&sessions))
+}
+
+#[tauri::command]
+pub async fn get_crash_reports(
+  app_handle: tauri::AppHandle,
+) -> Result<Vec<CrashReport>, CommandError> {
+  list_crash_reports(&app_handle).map_err(|err| {
+    log::error!("Unable to list crash reports: {}", err);
+    CommandError::GameManagement(format!("Unable to list crash reports: {err}"))
+  })
+}
+
 #[tauri::command]
 pub async fn uninstall_game(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
@@ -58,6 +150,11 @@ pub async fn uninstall_game(
     },
   }?;
 
+  // The shared ISO library copy is only removed once no mods are using it either
+  if let Err(err) = IsoLibrary::load(install_path).release(&game_name, &vanilla_reference()) {
+    log::error!("Unable to release game's ISO reference: {}", err);
+  }
+
   match std::fs::remove_dir_all(data_folder.join("out")) {
     Ok(_) => Ok(()),
     Err(e) => match e.kind() {
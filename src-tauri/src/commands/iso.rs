@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+  commands::binaries::InstallStepOutput,
+  config::LauncherConfig,
+  iso_library::{mod_reference, vanilla_reference, IsoLibrary, IsoLibraryEntry},
+};
+
+use super::CommandError;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsoLibraryImageInfo {
+  #[serde(flatten)]
+  pub image: IsoLibraryEntry,
+  pub extracted: bool,
+  pub source_exists: bool,
+}
+
+#[tauri::command]
+pub async fn list_iso_library(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: Option<String>,
+) -> Result<Vec<IsoLibraryImageInfo>, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => return Ok(Vec::new()),
+    Some(path) => Path::new(path),
+  };
+
+  let iso_library = IsoLibrary::load(install_path);
+  Ok(
+    iso_library
+      .images
+      .iter()
+      .filter(|image| game_name.as_ref().is_none_or(|game| &image.game == game))
+      .map(|image| IsoLibraryImageInfo {
+        extracted: image.is_extracted(iso_library.dir()),
+        source_exists: Path::new(&image.source_path).exists(),
+        image: image.clone(),
+      })
+      .collect(),
+  )
+}
+
+// Points an install at an image that is already extracted in the library, allowing
+// a (re)install to skip prompting for the ISO and the extraction step entirely
+#[tauri::command]
+pub async fn link_known_iso(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  game_name: String,
+  iso_id: String,
+  mod_name: Option<String>,
+  source_name: Option<String>,
+) -> Result<InstallStepOutput, CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => {
+      return Err(CommandError::IsoLibrary(
+        "No installation directory set, can't use ISO library".to_owned(),
+      ))
+    }
+    Some(path) => Path::new(path),
+  };
+
+  let mut iso_library = IsoLibrary::load(install_path);
+  match iso_library.get(&iso_id) {
+    Some(image) if image.game != game_name => {
+      return Ok(InstallStepOutput {
+        success: false,
+        msg: Some(format!(
+          "ISO '{}' is for {}, not {}",
+          iso_id, image.game, game_name
+        )),
+      });
+    }
+    Some(image) if !image.is_extracted(iso_library.dir()) => {
+      return Ok(InstallStepOutput {
+        success: false,
+        msg: Some(format!(
+          "ISO '{iso_id}' is no longer extracted, it must be extracted again"
+        )),
+      });
+    }
+    Some(_) => {}
+    None => {
+      return Ok(InstallStepOutput {
+        success: false,
+        msg: Some(format!("ISO '{iso_id}' is not in the library")),
+      });
+    }
+  }
+
+  let reference = match (&source_name, &mod_name) {
+    (Some(source_name), Some(mod_name)) => mod_reference(source_name, mod_name),
+    _ => vanilla_reference(),
+  };
+  iso_library
+    .add_reference(&iso_id, &reference)
+    .map_err(|err| {
+      log::error!("Unable to link ISO '{}': {}", iso_id, err);
+      CommandError::IsoLibrary(format!("Unable to link ISO '{iso_id}'"))
+    })?;
+  log::info!("Linked ISO '{}' to {} ({})", iso_id, game_name, reference);
+  Ok(InstallStepOutput {
+    success: true,
+    msg: None,
+  })
+}
+
+#[tauri::command]
+pub async fn remove_iso_from_library(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  iso_id: String,
+) -> Result<(), CommandError> {
+  let config_lock = config.lock().await;
+  let install_path = match &config_lock.installation_dir {
+    None => {
+      return Err(CommandError::IsoLibrary(
+        "No installation directory set, can't use ISO library".to_owned(),
+      ))
+    }
+    Some(path) => Path::new(path),
+  };
+
+  let mut iso_library = IsoLibrary::load(install_path);
+  iso_library.unregister(&iso_id).map_err(|err| {
+    log::error!("Unable to remove ISO '{}': {}", iso_id, err);
+    CommandError::IsoLibrary(err.to_string())
+  })?;
+  Ok(())
+}
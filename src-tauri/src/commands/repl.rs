@@ -0,0 +1,48 @@
+use crate::repl::{close, connect, send, ReplManager, ReplSessionInfo};
+
+use super::CommandError;
+
+#[tauri::command]
+pub async fn list_repl_sessions(
+  repl: tauri::State<'_, tokio::sync::Mutex<ReplManager>>,
+) -> Result<Vec<ReplSessionInfo>, CommandError> {
+  Ok(repl.lock().await.list())
+}
+
+// Attaches to a REPL that's already listening, for example a goalc started outside the launcher
+#[tauri::command]
+pub async fn connect_repl(
+  app_handle: tauri::AppHandle,
+  game_name: String,
+  address: String,
+) -> Result<ReplSessionInfo, CommandError> {
+  connect(&app_handle, address, &game_name)
+    .await
+    .map_err(|err| {
+      log::error!("Unable to connect to REPL: {}", err);
+      CommandError::Repl(format!("Unable to connect to REPL: {err}"))
+    })
+}
+
+#[tauri::command]
+pub async fn send_repl_form(
+  repl: tauri::State<'_, tokio::sync::Mutex<ReplManager>>,
+  session_id: u32,
+  form: String,
+) -> Result<(), CommandError> {
+  send(&repl, session_id, &form).await.map_err(|err| {
+    log::error!("Unable to send form to REPL: {}", err);
+    CommandError::Repl(format!("Unable to send form to REPL: {err}"))
+  })
+}
+
+#[tauri::command]
+pub async fn close_repl(
+  repl: tauri::State<'_, tokio::sync::Mutex<ReplManager>>,
+  session_id: u32,
+) -> Result<(), CommandError> {
+  close(&repl, session_id).await.map_err(|err| {
+    log::error!("Unable to close REPL: {}", err);
+    CommandError::Repl(format!("Unable to close REPL: {err}"))
+  })
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::{
   fs,
   io::{BufWriter, Write},
-  path::Path,
+  path::{Component, Path},
 };
 use sysinfo::{Disks, System};
 use tauri::Manager;
@@ -13,6 +13,8 @@ use zip::write::SimpleFileOptions;
 
 use crate::{
   config::LauncherConfig,
+  crash_reports::{crash_reports_dir, list_crash_reports},
+  iso_library::{vanilla_reference, IsoLibrary},
   util::{
     os::get_installed_vcc_runtime,
     zip::{append_dir_contents_to_zip, append_file_to_zip, check_if_zip_contains_top_level_file},
@@ -146,11 +148,15 @@ fn dump_per_game_info(
     .join("texture_replacements");
   package.game_info.get_game_info(game_name).has_texture_packs =
     texture_repl_dir.exists() && texture_repl_dir.read_dir().unwrap().next().is_some();
-  let build_info_path = active_version_dir
-    .join(game_name)
-    .join("data")
-    .join("iso_data")
-    .join(game_name)
+  let build_info_path = IsoLibrary::load(install_path)
+    .resolve_extracted_path(game_name, &vanilla_reference())
+    .unwrap_or_else(|| {
+      active_version_dir
+        .join(game_name)
+        .join("data")
+        .join("iso_data")
+        .join(game_name)
+    })
     .join("buildinfo.json");
   append_file_to_zip(
     zip_file,
@@ -269,6 +275,7 @@ pub async fn generate_support_package(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
   app_handle: tauri::AppHandle,
   user_path: String,
+  crash_report_id: Option<String>,
 ) -> Result<(), CommandError> {
   let mut package = SupportPackage::default();
   let config_lock = config.lock().await;
@@ -424,6 +431,38 @@ pub async fn generate_support_package(
     )
   })?;
 
+  // Attach the crash the user is asking for help with
+  if let Some(crash_report_id) = crash_report_id {
+    // only reports the launcher captured can be attached, the id must not lead anywhere else
+    let mut components = Path::new(&crash_report_id).components();
+    let is_single_name = matches!(
+      (components.next(), components.next()),
+      (Some(Component::Normal(_)), None)
+    );
+    let is_known_report = is_single_name
+      && list_crash_reports(&app_handle)
+        .map_err(|err| CommandError::Support(err.to_string()))?
+        .iter()
+        .any(|report| report.id == crash_report_id);
+    if !is_known_report {
+      return Err(CommandError::Support(format!(
+        "Crash report '{crash_report_id}' does not exist"
+      )));
+    }
+    let crash_dir = crash_reports_dir(&app_handle)
+      .map_err(|err| CommandError::Support(err.to_string()))?
+      .join(&crash_report_id);
+    append_dir_contents_to_zip(
+      &mut zip_file,
+      &crash_dir,
+      format!("Crash Reports/{crash_report_id}").as_str(),
+      vec!["log", "json", "txt", "gc"],
+    )
+    .map_err(|_| {
+      CommandError::Support("Unable to append the crash report to the support package".to_owned())
+    })?;
+  }
+
   // Dump High Level Info
   let options = SimpleFileOptions::default()
     .compression_method(zip::CompressionMethod::Deflated)
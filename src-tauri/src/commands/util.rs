@@ -1,5 +1,6 @@
 use crate::config::LauncherConfig;
 use crate::util::file::delete_dir;
+use crate::util::terminal::{available_terminals, resolve_terminal, Terminal};
 #[cfg(target_os = "macos")]
 use log::error;
 #[cfg(target_os = "macos")]
@@ -34,6 +35,20 @@ pub async fn delete_old_data_directory(app_handle: tauri::AppHandle) -> Result<(
   }
 }
 
+#[tauri::command]
+pub async fn list_available_terminals() -> Result<Vec<Terminal>, CommandError> {
+  Ok(available_terminals())
+}
+
+// The terminal external tools will be opened in, taking the user's preference into account
+#[tauri::command]
+pub async fn get_resolved_terminal(
+  config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+) -> Result<Option<Terminal>, CommandError> {
+  let config_lock = config.lock().await;
+  Ok(resolve_terminal(config_lock.preferred_terminal.as_deref()))
+}
+
 pub fn diskspace_threshold_for_fresh_install(game_name: &str) -> Result<u64, CommandError> {
   match game_name {
     "jak1" => Ok(4 * 1024 * 1024 * 1024),  // 4gb
@@ -6,11 +6,10 @@ use serde_json::Value;
 use crate::{
   config::LauncherConfig,
   util::{
+    archive::{emit_extraction_progress, extract_and_delete_archive},
     file::{create_dir, delete_dir},
     network::download_file,
     os::open_dir_in_os,
-    tar::extract_and_delete_tar_ball,
-    zip::extract_and_delete_zip_file,
   },
 };
 
@@ -67,6 +66,7 @@ pub async fn list_downloaded_versions(
 #[tauri::command]
 pub async fn download_version(
   config: tauri::State<'_, tokio::sync::Mutex<LauncherConfig>>,
+  app_handle: tauri::AppHandle,
   version: String,
   version_folder: String,
   url: String,
@@ -100,85 +100,54 @@ pub async fn download_version(
     ))
   })?;
 
-  if cfg!(windows) {
-    let download_path = install_path
-      .join("versions")
-      .join(&version_folder)
-      .join(format!("{version}.zip"));
-
-    // Download the file
-    download_file(&url, &download_path).await.map_err(|_| {
-      CommandError::VersionManagement("Unable to successfully download version".to_owned())
-    })?;
-
-    // Extract the zip file
-    extract_and_delete_zip_file(&download_path, &dest_dir, true).map_err(|_| {
-      CommandError::VersionManagement(
-        "Unable to successfully extract downloaded version".to_owned(),
-      )
-    })?;
+  // releases are zips on windows and tarballs elsewhere, but the format is detected after
+  // downloading rather than assumed
+  let download_path = install_path
+    .join("versions")
+    .join(&version_folder)
+    .join(format!("{version}.download"));
 
-    // Verify that the extracted files seem correct (look for extractor.exe)
-    let expected_extractor_path = dest_dir.join("extractor.exe");
-    if !expected_extractor_path.exists() {
-      log::info!(
-        "Version did not extract properly, {} is missing!",
-        expected_extractor_path.display()
-      );
-      delete_dir(&dest_dir).map_err(|_| {
-        CommandError::VersionManagement(format!(
-          "Unable to prepare destination folder '{}' for download",
-          dest_dir.display()
-        ))
-      })?;
-      return Err(CommandError::VersionManagement(
-        "Version did not extract properly, critical files are missing. An antivirus may have deleted the files!"
-        .to_owned()
-      ));
-    }
-    return Ok(());
-  } else if cfg!(unix) {
-    let download_path = install_path
-      .join("versions")
-      .join(&version_folder)
-      .join(format!("{version}.tar.gz"));
+  // Download the file
+  download_file(&url, &download_path).await.map_err(|_| {
+    CommandError::VersionManagement("Unable to successfully download version".to_owned())
+  })?;
 
-    // Download the file
-    download_file(&url, &download_path).await.map_err(|_| {
-      CommandError::VersionManagement("Unable to successfully download version".to_owned())
-    })?;
+  // Extract the archive
+  extract_and_delete_archive(
+    &download_path,
+    &dest_dir,
+    true,
+    &mut emit_extraction_progress(&app_handle),
+  )
+  .map_err(|err| {
+    log::error!("unable to extract and delete version archive {}", err);
+    CommandError::VersionManagement("Unable to successfully extract downloaded version".to_owned())
+  })?;
 
-    // Extract the zip file
-    extract_and_delete_tar_ball(&download_path, &dest_dir).map_err(|err| {
-      log::error!("unable to extract and delete version tar.gz file {}", err);
-      CommandError::VersionManagement(
-        "Unable to successfully extract downloaded version".to_owned(),
-      )
+  // Verify that the extracted files seem correct (look for the extractor)
+  let extractor_name = if cfg!(windows) {
+    "extractor.exe"
+  } else {
+    "extractor"
+  };
+  let expected_extractor_path = dest_dir.join(extractor_name);
+  if !expected_extractor_path.exists() {
+    log::info!(
+      "Version did not extract properly, {} is missing!",
+      expected_extractor_path.display()
+    );
+    delete_dir(&dest_dir).map_err(|_| {
+      CommandError::VersionManagement(format!(
+        "Unable to prepare destination folder '{}' for download",
+        dest_dir.display()
+      ))
     })?;
-
-    // Verify that the extracted files seem correct (look for extractor.exe)
-    let expected_extractor_path = dest_dir.join("extractor");
-    if !expected_extractor_path.exists() {
-      log::info!(
-        "Version did not extract properly, {} is missing!",
-        expected_extractor_path.display()
-      );
-      delete_dir(&dest_dir).map_err(|_| {
-        CommandError::VersionManagement(format!(
-          "Unable to prepare destination folder '{}' for download",
-          dest_dir.display()
-        ))
-      })?;
-      return Err(CommandError::VersionManagement(
-        "Version did not extract properly, critical files are missing. An antivirus may have deleted the files!"
-        .to_owned()
-      ));
-    }
-    return Ok(());
+    return Err(CommandError::VersionManagement(
+      "Version did not extract properly, critical files are missing. An antivirus may have deleted the files!"
+      .to_owned()
+    ));
   }
-  Err(CommandError::VersionManagement(
-    "Unknown operating system, unable to download and extract correct release".to_owned(),
-  ))
+  Ok(())
 }
 
 #[tauri::command]
@@ -0,0 +1,267 @@
+// Cache of previously compiled `active/<game>/data/out` directories
+//
+// Entries are keyed by the compile fingerprint (tooling version, game files, custom assets) so
+// switching back to a previously used version or texture pack combination can restore the
+// compiled output instead of running the compiler again.  Entries are moved in and out of the
+// cache rather than copied whenever possible, and the least recently used entries are evicted
+// once the cache grows beyond its size limit.
+
+use serde::{Deserialize, Serialize};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use crate::util::file::{create_dir, delete_dir, overwrite_dir};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CompileCacheError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error(transparent)]
+  FileSystem(#[from] fs_extra::error::Error),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileCacheEntry {
+  pub fingerprint: String,
+  pub game: String,
+  pub tooling_version: String,
+  pub texture_packs: Vec<String>,
+  pub size: u64,
+  pub last_used: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileCache {
+  #[serde(skip)]
+  cache_dir: PathBuf,
+  pub entries: Vec<CompileCacheEntry>,
+}
+
+impl CompileCache {
+  pub fn cache_dir(install_path: &Path) -> PathBuf {
+    install_path.join("cache").join("compiled")
+  }
+
+  pub fn load(install_path: &Path) -> CompileCache {
+    let cache_dir = Self::cache_dir(install_path);
+    let index_path = cache_dir.join("cache.json");
+    let mut cache = match fs::read_to_string(&index_path) {
+      Ok(content) => match serde_json::from_str::<CompileCache>(&content) {
+        Ok(cache) => cache,
+        Err(err) => {
+          log::error!(
+            "Unable to parse compiled output cache at {}, starting fresh: {}",
+            index_path.display(),
+            err
+          );
+          CompileCache::default()
+        }
+      },
+      Err(_) => CompileCache::default(),
+    };
+    cache.cache_dir = cache_dir;
+    // forget about anything that was removed out from under us
+    let cache_dir = cache.cache_dir.clone();
+    cache
+      .entries
+      .retain(|entry| entry_dir(&cache_dir, &entry.game, &entry.fingerprint).exists());
+    cache
+  }
+
+  pub fn save(&self) -> Result<(), CompileCacheError> {
+    create_dir(&self.cache_dir)?;
+    let file = fs::File::create(self.cache_dir.join("cache.json"))?;
+    serde_json::to_writer_pretty(file, &self)?;
+    Ok(())
+  }
+
+  pub fn contains(&self, game_name: &str, fingerprint: &str) -> bool {
+    self
+      .entries
+      .iter()
+      .any(|entry| entry.game == game_name && entry.fingerprint == fingerprint)
+  }
+
+  // Marks the entry as most recently used, so it survives any eviction that happens before it
+  // gets restored
+  pub fn touch(&mut self, game_name: &str, fingerprint: &str) {
+    if let Some(entry) = self
+      .entries
+      .iter_mut()
+      .find(|entry| entry.game == game_name && entry.fingerprint == fingerprint)
+    {
+      entry.last_used = chrono::Utc::now().to_rfc3339();
+    }
+  }
+
+  // Puts a copy of the compiled output into the cache, `out_dir` is moved rather than copied when
+  // `take` is set since the caller is about to replace it anyway
+  pub fn store(
+    &mut self,
+    out_dir: &Path,
+    entry: CompileCacheEntry,
+    take: bool,
+    size_limit: u64,
+  ) -> Result<(), CompileCacheError> {
+    if size_limit == 0 || !out_dir.exists() || self.contains(&entry.game, &entry.fingerprint) {
+      return Ok(());
+    }
+    let size = fs_extra::dir::get_size(out_dir)?;
+    if size > size_limit {
+      log::info!(
+        "Compiled output for {} is larger than the cache limit, not caching it",
+        entry.game
+      );
+      return Ok(());
+    }
+    let destination = entry_dir(&self.cache_dir, &entry.game, &entry.fingerprint);
+    log::info!(
+      "Caching compiled output for {} ({}) in {}",
+      entry.game,
+      entry.tooling_version,
+      destination.display()
+    );
+    if take {
+      move_dir(out_dir, &destination)?;
+    } else {
+      create_dir(&destination)?;
+      overwrite_dir(&out_dir.to_path_buf(), &destination)?;
+    }
+    self.entries.push(CompileCacheEntry {
+      size,
+      last_used: chrono::Utc::now().to_rfc3339(),
+      ..entry
+    });
+    self.evict(size_limit)?;
+    self.save()
+  }
+
+  // Moves a cached output into `out_dir`, returns false if there was nothing cached
+  pub fn restore(
+    &mut self,
+    game_name: &str,
+    fingerprint: &str,
+    out_dir: &Path,
+  ) -> Result<bool, CompileCacheError> {
+    if !self.contains(game_name, fingerprint) {
+      return Ok(false);
+    }
+    let source = entry_dir(&self.cache_dir, game_name, fingerprint);
+    log::info!("Restoring cached compiled output from {}", source.display());
+    delete_dir(out_dir)?;
+    move_dir(&source, out_dir)?;
+    self
+      .entries
+      .retain(|entry| !(entry.game == game_name && entry.fingerprint == fingerprint));
+    self.save()?;
+    Ok(true)
+  }
+
+  fn evict(&mut self, size_limit: u64) -> Result<(), CompileCacheError> {
+    // rfc3339 timestamps sort chronologically, oldest first
+    self.entries.sort_by(|a, b| a.last_used.cmp(&b.last_used));
+    let mut total: u64 = self.entries.iter().map(|entry| entry.size).sum();
+    while total > size_limit && !self.entries.is_empty() {
+      let entry = self.entries.remove(0);
+      log::info!(
+        "Evicting cached compiled output for {} ({})",
+        entry.game,
+        entry.tooling_version
+      );
+      delete_dir(entry_dir(&self.cache_dir, &entry.game, &entry.fingerprint))?;
+      total -= entry.size;
+    }
+    Ok(())
+  }
+}
+
+// Describes the compiled output currently sitting in an `out` directory, it lives inside `out` so
+// that removing the compiled output also forgets about it
+pub fn compiled_output_info_path(out_dir: &Path) -> PathBuf {
+  out_dir.join("launcher-compile-info.json")
+}
+
+pub fn read_compiled_output_info(out_dir: &Path) -> Option<CompileCacheEntry> {
+  let content = fs::read_to_string(compiled_output_info_path(out_dir)).ok()?;
+  serde_json::from_str(&content).ok()
+}
+
+pub fn write_compiled_output_info(
+  out_dir: &Path,
+  entry: &CompileCacheEntry,
+) -> Result<(), CompileCacheError> {
+  let file = fs::File::create(compiled_output_info_path(out_dir))?;
+  serde_json::to_writer_pretty(file, entry)?;
+  Ok(())
+}
+
+pub fn forget_compiled_output_info(out_dir: &Path) -> Result<(), std::io::Error> {
+  let info_path = compiled_output_info_path(out_dir);
+  if info_path.exists() {
+    fs::remove_file(info_path)?;
+  }
+  Ok(())
+}
+
+// Hangs onto the current output in case we come back to it, and restores a cached output if
+// these inputs have been compiled before.  Returns whether a cached output was restored, otherwise
+// `out_dir` is left ready for the compiler.
+pub fn swap_compiled_output(
+  install_path: &Path,
+  game_name: &str,
+  out_dir: &Path,
+  fingerprint: Option<&str>,
+  previous_output: Option<CompileCacheEntry>,
+  size_limit: u64,
+) -> Result<bool, std::io::Error> {
+  let mut compile_cache = CompileCache::load(install_path);
+  if let Some(fingerprint) = fingerprint {
+    if compile_cache.contains(game_name, fingerprint) {
+      compile_cache.touch(game_name, fingerprint);
+      if let Some(previous_output) = previous_output.clone() {
+        if let Err(err) = compile_cache.store(out_dir, previous_output, true, size_limit) {
+          log::warn!("Unable to cache the previous compiled output: {err}");
+        }
+      }
+      match compile_cache.restore(game_name, fingerprint, out_dir) {
+        Ok(true) => return Ok(true),
+        Ok(false) => {}
+        Err(err) => log::warn!("Unable to restore cached compiled output: {err}"),
+      }
+    }
+  }
+  if let Some(previous_output) = previous_output {
+    if out_dir.exists() {
+      if let Err(err) = compile_cache.store(out_dir, previous_output, false, size_limit) {
+        log::warn!("Unable to cache the previous compiled output: {err}");
+      }
+    }
+  }
+  // a failed or interrupted compile must not leave the old info behind
+  forget_compiled_output_info(out_dir)?;
+  Ok(false)
+}
+
+fn entry_dir(cache_dir: &Path, game_name: &str, fingerprint: &str) -> PathBuf {
+  cache_dir.join(game_name).join(fingerprint)
+}
+
+// Renames when possible, falling back to a copy if the directories are on different devices
+fn move_dir(src: &Path, dst: &Path) -> Result<(), CompileCacheError> {
+  delete_dir(dst)?;
+  if let Some(parent) = dst.parent() {
+    create_dir(&parent.to_path_buf())?;
+  }
+  if fs::rename(src, dst).is_err() {
+    create_dir(&dst.to_path_buf())?;
+    overwrite_dir(&src.to_path_buf(), &dst.to_path_buf())?;
+    delete_dir(src)?;
+  }
+  Ok(())
+}
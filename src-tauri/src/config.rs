@@ -9,6 +9,7 @@
 //
 // serde does not support defaultLiterals yet - https://github.com/serde-rs/serde/issues/368
 
+use crate::cache::{ModDependency, ModVersionSettings};
 use crate::util::file::create_dir;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
@@ -98,6 +99,61 @@ impl GameFeatureConfig {
   }
 }
 
+// Customizations applied when launching the game (or a mod), selected by name at launch time
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+  #[serde(default)]
+  pub gk_args: Vec<String>,
+  #[serde(default)]
+  pub passthrough_args: Vec<String>,
+  #[serde(default)]
+  pub env_vars: HashMap<String, String>,
+  // ie. `gamemoderun` or `mangohud --dlsym`, the game executable and its args are appended
+  #[serde(default)]
+  pub wrapper_command: Vec<String>,
+  #[serde(default)]
+  pub executable_path: Option<String>,
+}
+
+impl LaunchProfile {
+  // gk args go before the `--` separator, the passthrough args are handed to the game itself
+  pub fn apply_args(&self, mut args: Vec<String>) -> Vec<String> {
+    match args.iter().position(|arg| arg == "--") {
+      Some(separator) => {
+        args.splice(separator..separator, self.gk_args.iter().cloned());
+      }
+      // old argument format, everything goes straight to gk
+      None => args.extend(self.gk_args.iter().cloned()),
+    }
+    args.extend(self.passthrough_args.iter().cloned());
+    args
+  }
+
+  // Returns the program to run and its arguments, taking the wrapper command into account
+  pub fn command_line(&self, executable_path: &Path, args: Vec<String>) -> (PathBuf, Vec<String>) {
+    match self.wrapper_command.split_first() {
+      Some((wrapper, wrapper_args)) => {
+        let mut full_args = wrapper_args.to_vec();
+        full_args.push(executable_path.to_string_lossy().into_owned());
+        full_args.extend(args);
+        (PathBuf::from(wrapper), full_args)
+      }
+      None => (executable_path.to_path_buf(), args),
+    }
+  }
+
+  pub fn env_prefix(&self) -> String {
+    let mut vars: Vec<String> = self
+      .env_vars
+      .iter()
+      .map(|(key, value)| format!("{key}={value} "))
+      .collect();
+    vars.sort();
+    vars.concat()
+  }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GameConfig {
@@ -106,6 +162,20 @@ pub struct GameConfig {
   pub features: GameFeatureConfig,
   pub seconds_played: u64,
   pub mods_installed_version: HashMap<String, HashMap<String, String>>,
+  // source -> mod -> seconds
+  #[serde(default)]
+  pub mods_seconds_played: HashMap<String, HashMap<String, u64>>,
+  #[serde(default)]
+  pub launch_profiles: HashMap<String, LaunchProfile>,
+  // source -> mod -> profile name
+  #[serde(default)]
+  pub mod_launch_profiles: HashMap<String, HashMap<String, HashMap<String, LaunchProfile>>>,
+  // source -> mod -> the dependencies of the installed version
+  #[serde(default)]
+  pub mods_dependencies: HashMap<String, HashMap<String, Vec<ModDependency>>>,
+  // source -> mod -> the settings of the installed version
+  #[serde(default)]
+  pub mods_settings: HashMap<String, HashMap<String, ModVersionSettings>>,
 }
 
 impl GameConfig {
@@ -116,6 +186,11 @@ impl GameConfig {
       features: GameFeatureConfig::default(),
       seconds_played: 0,
       mods_installed_version: HashMap::new(),
+      mods_seconds_played: HashMap::new(),
+      launch_profiles: HashMap::new(),
+      mod_launch_profiles: HashMap::new(),
+      mods_dependencies: HashMap::new(),
+      mods_settings: HashMap::new(),
     }
   }
 
@@ -126,6 +201,36 @@ impl GameConfig {
   pub fn version(&self) -> Option<String> {
     self.version.clone()
   }
+
+  pub fn launch_profiles_for(
+    &self,
+    mod_info: Option<(&str, &str)>,
+  ) -> HashMap<String, LaunchProfile> {
+    match mod_info {
+      Some((source_name, mod_name)) => self
+        .mod_launch_profiles
+        .get(source_name)
+        .and_then(|mods| mods.get(mod_name))
+        .cloned()
+        .unwrap_or_default(),
+      None => self.launch_profiles.clone(),
+    }
+  }
+
+  fn launch_profiles_for_mut(
+    &mut self,
+    mod_info: Option<(&str, &str)>,
+  ) -> &mut HashMap<String, LaunchProfile> {
+    match mod_info {
+      Some((source_name, mod_name)) => self
+        .mod_launch_profiles
+        .entry(source_name.to_owned())
+        .or_default()
+        .entry(mod_name.to_owned())
+        .or_default(),
+      None => &mut self.launch_profiles,
+    }
+  }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -186,12 +291,35 @@ pub struct LauncherConfig {
   pub proceed_after_successful_operation: bool,
   pub auto_update_games: bool,
   pub delete_previous_versions: bool,
+  #[serde(default = "default_compiled_output_cache_size_mb")]
+  pub compiled_output_cache_size_mb: u64,
+  // how many previous app, game and extractor logs are kept around
+  #[serde(default = "default_log_retention_count")]
+  pub log_retention_count: usize,
+  // how many previously installed versions of each mod are kept around to switch back to
+  #[serde(default = "default_mod_version_retention_count")]
+  pub mod_version_retention_count: usize,
+  // terminal emulator used to open external tools on Linux, a name on the PATH or a full path
+  #[serde(default)]
+  pub preferred_terminal: Option<String>,
 }
 
 fn default_version() -> String {
   "2.0".to_owned()
 }
 
+fn default_compiled_output_cache_size_mb() -> u64 {
+  8192
+}
+
+fn default_log_retention_count() -> usize {
+  5
+}
+
+fn default_mod_version_retention_count() -> usize {
+  2
+}
+
 fn migrate_old_config(json_value: serde_json::Value, settings_path: PathBuf) -> LauncherConfig {
   log::warn!("Outdated config detected. Migrating to the latest version.");
   let mut new_config = LauncherConfig::default(Some(settings_path));
@@ -227,6 +355,18 @@ fn migrate_old_config(json_value: serde_json::Value, settings_path: PathBuf) ->
           game_config.mods_installed_version =
             serde_json::from_value(mods.clone()).unwrap_or_default();
         }
+        if let Some(mods_played) = value.get("modsSecondsPlayed") {
+          game_config.mods_seconds_played =
+            serde_json::from_value(mods_played.clone()).unwrap_or_default();
+        }
+        if let Some(profiles) = value.get("launchProfiles") {
+          game_config.launch_profiles =
+            serde_json::from_value(profiles.clone()).unwrap_or_default();
+        }
+        if let Some(profiles) = value.get("modLaunchProfiles") {
+          game_config.mod_launch_profiles =
+            serde_json::from_value(profiles.clone()).unwrap_or_default();
+        }
 
         new_config.games.insert(supported_game, game_config);
       } else {
@@ -285,6 +425,28 @@ fn migrate_old_config(json_value: serde_json::Value, settings_path: PathBuf) ->
     .and_then(|v| v.as_bool())
     .unwrap_or(false);
 
+  new_config.compiled_output_cache_size_mb = json_value
+    .get("compiledOutputCacheSizeMb")
+    .and_then(|v| v.as_u64())
+    .unwrap_or(default_compiled_output_cache_size_mb());
+
+  new_config.log_retention_count = json_value
+    .get("logRetentionCount")
+    .and_then(|v| v.as_u64())
+    .map(|v| v as usize)
+    .unwrap_or(default_log_retention_count());
+
+  new_config.mod_version_retention_count = json_value
+    .get("modVersionRetentionCount")
+    .and_then(|v| v.as_u64())
+    .map(|v| v as usize)
+    .unwrap_or(default_mod_version_retention_count());
+
+  new_config.preferred_terminal = json_value
+    .get("preferredTerminal")
+    .and_then(|v| v.as_str())
+    .map(String::from);
+
   log::info!("Migration complete. New configuration ready.");
   new_config
 }
@@ -310,6 +472,10 @@ impl LauncherConfig {
       proceed_after_successful_operation: true,
       auto_update_games: false,
       delete_previous_versions: false,
+      compiled_output_cache_size_mb: default_compiled_output_cache_size_mb(),
+      log_retention_count: default_log_retention_count(),
+      mod_version_retention_count: default_mod_version_retention_count(),
+      preferred_terminal: None,
     }
   }
 
@@ -459,6 +625,12 @@ impl LauncherConfig {
         }
         "active_version" => self.active_version = val.as_str().map(|s| s.to_string()),
         "locale" => self.locale = val.as_str().map(|s| s.to_string()),
+        "preferred_terminal" => {
+          self.preferred_terminal = val
+            .as_str()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_string())
+        }
         "check_for_latest_mod_version" => {
           self.check_for_latest_mod_version = val.as_bool().unwrap_or(true)
         }
@@ -466,6 +638,23 @@ impl LauncherConfig {
         "delete_previous_versions" => {
           self.delete_previous_versions = val.as_bool().unwrap_or(false)
         }
+        "compiled_output_cache_size_mb" => {
+          self.compiled_output_cache_size_mb = val
+            .as_u64()
+            .unwrap_or(default_compiled_output_cache_size_mb())
+        }
+        "log_retention_count" => {
+          self.log_retention_count = val
+            .as_u64()
+            .map(|v| v as usize)
+            .unwrap_or(default_log_retention_count())
+        }
+        "mod_version_retention_count" => {
+          self.mod_version_retention_count = val
+            .as_u64()
+            .map(|v| v as usize)
+            .unwrap_or(default_mod_version_retention_count())
+        }
         "rip_levels" => {
           self.decompiler_settings.rip_levels_enabled = val.as_bool().unwrap_or(false)
         }
@@ -514,6 +703,7 @@ impl LauncherConfig {
         "active_texture_packs" => Ok(json!(game_config.active_texture_packs())),
         "seconds_played" => Ok(json!(game_config.seconds_played)),
         "installed_mods" => Ok(json!(game_config.mods_installed_version)),
+        "mods_seconds_played" => Ok(json!(game_config.mods_seconds_played)),
         _ => {
           log::error!("Key '{}' not recognized", key);
           Err(ConfigError::Configuration("Invalid key".to_owned()))
@@ -535,6 +725,12 @@ impl LauncherConfig {
             .as_ref()
             .map_or(Value::Null, |v| Value::String(v.clone())),
         ),
+        "preferred_terminal" => Ok(
+          self
+            .preferred_terminal
+            .as_ref()
+            .map_or(Value::Null, |v| Value::String(v.clone())),
+        ),
         "locale" => Ok(
           self
             .locale
@@ -548,6 +744,9 @@ impl LauncherConfig {
         }
         "auto_update_games" => Ok(Value::Bool(self.auto_update_games)),
         "delete_previous_versions" => Ok(Value::Bool(self.delete_previous_versions)),
+        "compiled_output_cache_size_mb" => Ok(json!(self.compiled_output_cache_size_mb)),
+        "log_retention_count" => Ok(json!(self.log_retention_count)),
+        "mod_version_retention_count" => Ok(json!(self.mod_version_retention_count)),
         "rip_levels" => Ok(Value::Bool(self.decompiler_settings.rip_levels_enabled)),
         "rip_collision" => Ok(Value::Bool(self.decompiler_settings.rip_collision_enabled)),
         "rip_textures" => Ok(Value::Bool(self.decompiler_settings.rip_textures_enabled)),
@@ -593,6 +792,14 @@ impl LauncherConfig {
           .mods_installed_version
           .get_mut(&source)
           .map(|mods| mods.remove(&mod_name));
+        game_config
+          .mods_dependencies
+          .get_mut(&source)
+          .map(|mods| mods.remove(&mod_name));
+        game_config
+          .mods_settings
+          .get_mut(&source)
+          .map(|mods| mods.remove(&mod_name));
       }
       _ => todo!(),
     }
@@ -601,6 +808,147 @@ impl LauncherConfig {
     Ok(())
   }
 
+  pub fn set_mod_dependencies(
+    &mut self,
+    game_name: &String,
+    source_name: &str,
+    mod_name: &str,
+    dependencies: Vec<ModDependency>,
+  ) -> Result<(), ConfigError> {
+    let game_config = self.get_supported_game_config_mut(game_name)?;
+    let source_dependencies = game_config
+      .mods_dependencies
+      .entry(source_name.to_owned())
+      .or_default();
+    if dependencies.is_empty() {
+      source_dependencies.remove(mod_name);
+    } else {
+      source_dependencies.insert(mod_name.to_owned(), dependencies);
+    }
+    self.save_config()?;
+    Ok(())
+  }
+
+  pub fn set_mod_settings(
+    &mut self,
+    game_name: &String,
+    source_name: &str,
+    mod_name: &str,
+    settings: ModVersionSettings,
+  ) -> Result<(), ConfigError> {
+    let game_config = self.get_supported_game_config_mut(game_name)?;
+    let source_settings = game_config
+      .mods_settings
+      .entry(source_name.to_owned())
+      .or_default();
+    if settings == ModVersionSettings::default() {
+      source_settings.remove(mod_name);
+    } else {
+      source_settings.insert(mod_name.to_owned(), settings);
+    }
+    self.save_config()?;
+    Ok(())
+  }
+
+  pub fn installed_mod_version(
+    &self,
+    game_name: &str,
+    source_name: &str,
+    mod_name: &str,
+  ) -> Option<String> {
+    self
+      .game_config(game_name)
+      .and_then(|game_config| game_config.mods_installed_version.get(source_name))
+      .and_then(|mods| mods.get(mod_name))
+      .cloned()
+  }
+
+  // Settings of the installed version of a mod, the defaults if it has none
+  pub fn mod_settings(
+    &self,
+    game_name: &str,
+    source_name: &str,
+    mod_name: &str,
+  ) -> ModVersionSettings {
+    self
+      .game_config(game_name)
+      .and_then(|game_config| game_config.mods_settings.get(source_name))
+      .and_then(|mods| mods.get(mod_name))
+      .cloned()
+      .unwrap_or_default()
+  }
+
+  pub fn add_mod_playtime(
+    &mut self,
+    game_name: &String,
+    source_name: &str,
+    mod_name: &str,
+    seconds: u64,
+  ) -> Result<(), ConfigError> {
+    let game_config = self.get_supported_game_config_mut(game_name)?;
+    *game_config
+      .mods_seconds_played
+      .entry(source_name.to_owned())
+      .or_default()
+      .entry(mod_name.to_owned())
+      .or_insert(0) += seconds;
+    self.save_config()?;
+    Ok(())
+  }
+
+  pub fn game_config(&self, game_name: &str) -> Option<&GameConfig> {
+    SupportedGame::from_str(game_name)
+      .ok()
+      .and_then(|game| self.games.get(&game))
+  }
+
+  pub fn get_launch_profile(
+    &self,
+    game_name: &str,
+    mod_info: Option<(&str, &str)>,
+    profile_name: &str,
+  ) -> Result<LaunchProfile, ConfigError> {
+    self
+      .game_config(game_name)
+      .and_then(|game_config| {
+        game_config
+          .launch_profiles_for(mod_info)
+          .remove(profile_name)
+      })
+      .ok_or_else(|| {
+        ConfigError::Configuration(format!("Launch profile '{profile_name}' does not exist"))
+      })
+  }
+
+  pub fn save_launch_profile(
+    &mut self,
+    game_name: &String,
+    mod_info: Option<(&str, &str)>,
+    profile_name: String,
+    profile: LaunchProfile,
+  ) -> Result<(), ConfigError> {
+    let game_config = self.get_supported_game_config_mut(game_name)?;
+    game_config
+      .launch_profiles_for_mut(mod_info)
+      .insert(profile_name, profile);
+    self.save_config()?;
+    Ok(())
+  }
+
+  pub fn delete_launch_profile(
+    &mut self,
+    game_name: &String,
+    mod_info: Option<(&str, &str)>,
+    profile_name: &str,
+  ) -> Result<(), ConfigError> {
+    let game_config = self.get_supported_game_config_mut(game_name)?;
+    game_config
+      .launch_profiles_for_mut(mod_info)
+      .remove(profile_name);
+    self.save_config()?;
+    Ok(())
+  }
+
   pub fn cleanup_game_enabled_texture_packs(
     &mut self,
     game_name: &String,
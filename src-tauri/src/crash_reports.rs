@@ -0,0 +1,205 @@
+// Crash reports captured when a game exits abnormally
+//
+// Each crash gets a timestamped folder in `<app_log_dir>/crashes` holding the tail of the
+// launcher's game log, the game's own logs, its settings and a `crash-info.json` describing what
+// was running.  Only the most recent crashes are kept around.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use walkdir::WalkDir;
+
+use crate::{
+  process_registry::RunningGame,
+  util::file::{create_dir, delete_dir, read_last_lines},
+};
+
+const MAX_CRASH_REPORTS: usize = 20;
+const LOG_TAIL_LINES: usize = 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CrashReportError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error("{0}")]
+  Location(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+  pub id: String,
+  pub game_name: String,
+  pub mod_name: Option<String>,
+  pub source_name: Option<String>,
+  pub version: Option<String>,
+  pub launcher_version: String,
+  pub exit_code: Option<i32>,
+  pub start_time: String,
+  pub crash_time: String,
+}
+
+pub fn crash_reports_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, CrashReportError> {
+  app_handle
+    .path()
+    .app_log_dir()
+    .map(|log_dir| log_dir.join("crashes"))
+    .map_err(|_| CrashReportError::Location("Couldn't determine launcher log directory".to_owned()))
+}
+
+// Copies the files with one of the given extensions, keeping their layout relative to `src`
+fn copy_matching_files(src: &Path, dst: &Path, extensions: &[&str]) -> Result<(), std::io::Error> {
+  if !src.exists() {
+    return Ok(());
+  }
+  for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+    let path = entry.path();
+    let matches = path
+      .extension()
+      .and_then(|ext| ext.to_str())
+      .is_some_and(|ext| extensions.contains(&ext));
+    if !path.is_file() || !matches {
+      continue;
+    }
+    let destination = dst.join(path.strip_prefix(src).unwrap_or(path));
+    if let Some(parent) = destination.parent() {
+      create_dir(&parent.to_path_buf())?;
+    }
+    fs::copy(path, destination)?;
+  }
+  Ok(())
+}
+
+pub fn capture_crash(
+  app_handle: &tauri::AppHandle,
+  game: &RunningGame,
+  install_path: Option<&Path>,
+  exit_code: Option<i32>,
+) -> Result<CrashReport, CrashReportError> {
+  let crash_time = chrono::Local::now();
+  let mut id = format!("{}-{}", crash_time.format("%Y%m%d-%H%M%S"), game.game_name);
+  if let Some(mod_name) = &game.mod_name {
+    id = format!("{id}-{mod_name}");
+  }
+  // concurrent instances can crash within the same second
+  id = format!("{id}-{}", game.pid);
+  let crashes_dir = crash_reports_dir(app_handle)?;
+  let crash_dir = crashes_dir.join(&id);
+  create_dir(&crash_dir)?;
+  log::info!("Capturing crash report in {}", crash_dir.display());
+
+  if let Some(log_file) = &game.log_file {
+    match read_last_lines(Path::new(log_file), LOG_TAIL_LINES) {
+      Ok(lines) => fs::write(crash_dir.join("game.log"), lines.join("\n"))?,
+      Err(err) => log::warn!("Unable to read the tail of {}: {}", log_file, err),
+    }
+  }
+
+  if let Some(install_path) = install_path {
+    let game_dir = match (&game.source_name, &game.mod_name) {
+      (Some(source_name), Some(mod_name)) => install_path
+        .join("features")
+        .join(&game.game_name)
+        .join("mods")
+        .join(source_name)
+        .join(mod_name),
+      _ => install_path.join("active").join(&game.game_name),
+    };
+    copy_matching_files(
+      &game_dir.join("data").join("log"),
+      &crash_dir.join("data-log"),
+      &["log", "json", "txt"],
+    )?;
+    if let (Some(source_name), Some(mod_name)) = (&game.source_name, &game.mod_name) {
+      copy_matching_files(
+        &install_path
+          .join("features")
+          .join(&game.game_name)
+          .join("mods")
+          .join(source_name)
+          .join("_settings")
+          .join(mod_name),
+        &crash_dir.join("settings"),
+        &["gc", "json"],
+      )?;
+    }
+  }
+  if game.mod_name.is_none() {
+    if let Ok(config_dir) = app_handle.path().config_dir() {
+      copy_matching_files(
+        &config_dir
+          .join("OpenGOAL")
+          .join(&game.game_name)
+          .join("settings"),
+        &crash_dir.join("settings"),
+        &["gc", "json"],
+      )?;
+    }
+  }
+  if let Ok(launcher_config_dir) = app_handle.path().app_config_dir() {
+    let launcher_settings = launcher_config_dir.join("settings.json");
+    if launcher_settings.exists() {
+      fs::copy(launcher_settings, crash_dir.join("launcher-settings.json"))?;
+    }
+  }
+
+  let report = CrashReport {
+    id,
+    game_name: game.game_name.clone(),
+    mod_name: game.mod_name.clone(),
+    source_name: game.source_name.clone(),
+    version: game.version.clone(),
+    launcher_version: app_handle.package_info().version.to_string(),
+    exit_code,
+    start_time: game.start_time.clone(),
+    crash_time: crash_time.to_rfc3339(),
+  };
+  let file = fs::File::create(crash_dir.join("crash-info.json"))?;
+  serde_json::to_writer_pretty(file, &report)?;
+
+  // only keep the most recent crashes
+  for old_report in list_crash_reports(app_handle)?
+    .iter()
+    .skip(MAX_CRASH_REPORTS)
+  {
+    log::info!("Removing old crash report {}", old_report.id);
+    delete_dir(crashes_dir.join(&old_report.id))?;
+  }
+  Ok(report)
+}
+
+// Most recent crashes first
+pub fn list_crash_reports(
+  app_handle: &tauri::AppHandle,
+) -> Result<Vec<CrashReport>, CrashReportError> {
+  let crashes_dir = crash_reports_dir(app_handle)?;
+  if !crashes_dir.exists() {
+    return Ok(vec![]);
+  }
+  let mut reports = Vec::new();
+  for entry in fs::read_dir(&crashes_dir)? {
+    let info_path = entry?.path().join("crash-info.json");
+    let report = fs::read_to_string(&info_path)
+      .ok()
+      .and_then(|content| serde_json::from_str::<CrashReport>(&content).ok());
+    match report {
+      Some(report) => reports.push(report),
+      None => log::warn!("Skipping unreadable crash report {}", info_path.display()),
+    }
+  }
+  // the local offset can differ between crashes (DST, travelling), so compare actual instants
+  reports.sort_by_key(|report| {
+    std::cmp::Reverse(
+      chrono::DateTime::parse_from_rfc3339(&report.crash_time)
+        .ok()
+        .map(|crash_time| crash_time.with_timezone(&chrono::Utc)),
+    )
+  });
+  Ok(reports)
+}
@@ -0,0 +1,160 @@
+// Cache of the cover and thumbnail art of mods from remote mod sources
+//
+// Art is downloaded once and stored in the app's cache directory under the hash of its contents,
+// so mods that share the same image only store it once, with an index mapping each url to its
+// file.  The frontend loads the files through the asset protocol, which keeps the mod list fast
+// and working offline.  Anything that isn't a recognizable image is rejected rather than stored.
+
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::util::file::{create_dir, get_image_file_type};
+
+const INDEX_FILE_NAME: &str = "index.json";
+// cover art is the biggest thing we expect, nothing legitimate comes close to this
+const MAX_IMAGE_SIZE: usize = 20 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImageCacheError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error(transparent)]
+  NetworkRequest(#[from] reqwest::Error),
+  #[error("{0}")]
+  InvalidImage(String),
+  #[error("{0}")]
+  Unavailable(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageCacheEntry {
+  // name of the file in the cache directory, the hash of its contents and its extension
+  pub file_name: String,
+  pub downloaded_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageCache {
+  #[serde(skip)]
+  cache_dir: Option<PathBuf>,
+  pub entries: HashMap<String, ImageCacheEntry>,
+}
+
+impl ImageCache {
+  pub fn load(cache_dir: Option<PathBuf>) -> ImageCache {
+    let cache_dir = match cache_dir {
+      Some(cache_dir) => cache_dir,
+      None => {
+        log::warn!("No cache directory available, mod art won't be cached");
+        return ImageCache::default();
+      }
+    };
+    let index_path = cache_dir.join(INDEX_FILE_NAME);
+    let mut cache = match fs::read_to_string(&index_path) {
+      Ok(content) => match serde_json::from_str::<ImageCache>(&content) {
+        Ok(cache) => cache,
+        Err(err) => {
+          log::error!(
+            "Unable to parse image cache at {}, starting fresh: {}",
+            index_path.display(),
+            err
+          );
+          ImageCache::default()
+        }
+      },
+      Err(_) => ImageCache::default(),
+    };
+    // forget about anything that was removed out from under us
+    cache
+      .entries
+      .retain(|_, entry| cache_dir.join(&entry.file_name).is_file());
+    cache.cache_dir = Some(cache_dir);
+    cache
+  }
+
+  fn cache_dir(&self) -> Result<&Path, ImageCacheError> {
+    self
+      .cache_dir
+      .as_deref()
+      .ok_or_else(|| ImageCacheError::Unavailable("There is no cache directory".to_owned()))
+  }
+
+  pub fn save(&self) -> Result<(), ImageCacheError> {
+    let cache_dir = self.cache_dir()?.to_path_buf();
+    create_dir(&cache_dir)?;
+    let file = fs::File::create(cache_dir.join(INDEX_FILE_NAME))?;
+    serde_json::to_writer_pretty(file, &self)?;
+    Ok(())
+  }
+
+  pub fn cached_path(&self, url: &str) -> Option<PathBuf> {
+    let cache_dir = self.cache_dir.as_ref()?;
+    self
+      .entries
+      .get(url)
+      .map(|entry| cache_dir.join(&entry.file_name))
+      .filter(|path| path.is_file())
+  }
+
+  // Stores a downloaded image, returning where it can be loaded from
+  pub fn store(&mut self, url: &str, contents: &[u8]) -> Result<PathBuf, ImageCacheError> {
+    let extension = get_image_file_type(contents)
+      .ok_or_else(|| ImageCacheError::InvalidImage(format!("{url} is not a supported image")))?;
+    let cache_dir = self.cache_dir()?.to_path_buf();
+    create_dir(&cache_dir)?;
+    let file_name = format!("{:x}.{extension}", Sha256::digest(contents));
+    let path = cache_dir.join(&file_name);
+    if !path.is_file() {
+      // written under a temporary name first so a partial file is never served
+      let partial_path = cache_dir.join(format!("{file_name}.partial"));
+      fs::write(&partial_path, contents)?;
+      fs::rename(&partial_path, &path)?;
+    }
+    self.entries.insert(
+      url.to_owned(),
+      ImageCacheEntry {
+        file_name,
+        downloaded_at: chrono::Utc::now().to_rfc3339(),
+      },
+    );
+    self.save()?;
+    Ok(path)
+  }
+}
+
+pub async fn download_image(url: &str) -> Result<Vec<u8>, ImageCacheError> {
+  let client = reqwest::Client::builder()
+    .timeout(DOWNLOAD_TIMEOUT)
+    .build()?;
+  let mut response = client.get(url).send().await?.error_for_status()?;
+  if response
+    .content_length()
+    .is_some_and(|length| length as usize > MAX_IMAGE_SIZE)
+  {
+    return Err(ImageCacheError::InvalidImage(format!(
+      "{url} is too big to be mod art"
+    )));
+  }
+  let mut contents = Vec::new();
+  while let Some(chunk) = response.chunk().await? {
+    contents.extend_from_slice(&chunk);
+    if contents.len() > MAX_IMAGE_SIZE {
+      return Err(ImageCacheError::InvalidImage(format!(
+        "{url} is too big to be mod art"
+      )));
+    }
+  }
+  Ok(contents)
+}
@@ -0,0 +1,389 @@
+// Registry of the game images (ISO files or extracted folders) the user has provided
+//
+// Each unique image (by content hash) is extracted once into `<install_dir>/iso/<id>/<game>`
+// and shared between the vanilla install and any mod installs that need it.  Installs
+// register a reference against the image they use, and the extracted copy is only removed
+// once nothing references it anymore.
+//
+// The source image itself is remembered so that a reinstall can happen without prompting
+// the user for the ISO again.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  time::UNIX_EPOCH,
+};
+
+use crate::util::file::{create_dir, delete_dir, hash_dir_contents, hash_file_contents};
+
+#[derive(Debug, thiserror::Error)]
+pub enum IsoLibraryError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error("{0}")]
+  Library(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IsoLibraryEntry {
+  pub id: String,
+  pub game: String,
+  pub region: Option<String>,
+  pub serial: Option<String>,
+  pub hash: String,
+  pub source_path: String,
+  pub source_size: u64,
+  pub source_modified: u64,
+  pub registered_date: String,
+  pub references: Vec<String>,
+}
+
+impl IsoLibraryEntry {
+  pub fn extraction_dir(&self, library_dir: &Path) -> PathBuf {
+    library_dir.join(&self.id)
+  }
+
+  pub fn extracted_path(&self, library_dir: &Path) -> PathBuf {
+    self.extraction_dir(library_dir).join(&self.game)
+  }
+
+  pub fn is_extracted(&self, library_dir: &Path) -> bool {
+    self.extracted_path(library_dir).exists()
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IsoBuildInfo {
+  serial: Option<String>,
+}
+
+pub fn id_for_hash(hash: &str) -> String {
+  hash.chars().take(16).collect()
+}
+
+pub fn vanilla_reference() -> String {
+  "vanilla".to_owned()
+}
+
+pub fn mod_reference(source_name: &str, mod_name: &str) -> String {
+  format!("mod:{source_name}/{mod_name}")
+}
+
+fn region_from_serial(serial: &str) -> Option<String> {
+  let prefix: String = serial.chars().take(4).collect::<String>().to_uppercase();
+  match prefix.as_str() {
+    "SCUS" | "SLUS" => Some("NTSC-U".to_owned()),
+    "SCES" | "SLES" => Some("PAL".to_owned()),
+    "SCPS" | "SLPS" | "SCPM" => Some("NTSC-J".to_owned()),
+    "SCKA" => Some("NTSC-K".to_owned()),
+    _ => None,
+  }
+}
+
+// Hashes either a single image file, or every file within an already extracted folder
+pub fn hash_image_source(source: &Path) -> Result<String, IsoLibraryError> {
+  let mut hasher = Sha256::new();
+  if source.is_dir() {
+    hash_dir_contents(&mut hasher, source)?;
+  } else {
+    hash_file_contents(&mut hasher, source)?;
+  }
+  Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn source_fingerprint(source: &Path) -> (u64, u64) {
+  match fs::metadata(source) {
+    Ok(metadata) => {
+      let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+      (metadata.len(), modified)
+    }
+    Err(_) => (0, 0),
+  }
+}
+
+pub struct PreparedImage {
+  pub id: String,
+  pub hash: String,
+  pub extraction_dir: PathBuf,
+  pub already_extracted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct IsoLibrary {
+  #[serde(skip)]
+  library_dir: PathBuf,
+  pub images: Vec<IsoLibraryEntry>,
+}
+
+impl IsoLibrary {
+  pub fn library_dir(install_path: &Path) -> PathBuf {
+    install_path.join("iso")
+  }
+
+  // Scratch space for images that have to be converted before they can be extracted
+  pub fn temp_dir(install_path: &Path) -> PathBuf {
+    Self::library_dir(install_path).join("tmp")
+  }
+
+  pub fn load(install_path: &Path) -> IsoLibrary {
+    let library_dir = Self::library_dir(install_path);
+    let index_path = library_dir.join("library.json");
+    let mut library = match fs::read_to_string(&index_path) {
+      Ok(content) => match serde_json::from_str::<IsoLibrary>(&content) {
+        Ok(library) => library,
+        Err(err) => {
+          log::error!(
+            "Unable to parse ISO library at {}, starting fresh: {}",
+            index_path.display(),
+            err
+          );
+          IsoLibrary::default()
+        }
+      },
+      Err(_) => IsoLibrary::default(),
+    };
+    library.library_dir = library_dir;
+    library
+  }
+
+  pub fn save(&self) -> Result<(), IsoLibraryError> {
+    create_dir(&self.library_dir)?;
+    let file = fs::File::create(self.library_dir.join("library.json"))?;
+    serde_json::to_writer_pretty(file, &self)?;
+    Ok(())
+  }
+
+  pub fn dir(&self) -> &Path {
+    &self.library_dir
+  }
+
+  pub fn get(&self, id: &str) -> Option<&IsoLibraryEntry> {
+    self.images.iter().find(|image| image.id == id)
+  }
+
+  // Finds a registered image that came from the provided source, re-using the previously
+  // computed hash when the source hasn't changed on disk
+  pub fn find_by_source(&self, game_name: &str, source: &Path) -> Option<&IsoLibraryEntry> {
+    let (size, modified) = source_fingerprint(source);
+    let source_path = source.to_string_lossy();
+    self.images.iter().find(|image| {
+      image.game == game_name
+        && image.source_path == source_path
+        && image.source_size == size
+        && image.source_modified == modified
+    })
+  }
+
+  pub fn find_by_hash(&self, game_name: &str, hash: &str) -> Option<&IsoLibraryEntry> {
+    self
+      .images
+      .iter()
+      .find(|image| image.game == game_name && image.hash == hash)
+  }
+
+  // Returns the extracted entry that should be used for the given install.  When the image the
+  // install references is no longer extracted, only another extracted copy of the same image will
+  // do, a different dump of the game could be another region or revision.
+  pub fn resolve(&self, game_name: &str, reference: &str) -> Option<&IsoLibraryEntry> {
+    let referenced = self
+      .images
+      .iter()
+      .find(|image| image.game == game_name && image.references.iter().any(|r| r == reference))?;
+    if referenced.is_extracted(&self.library_dir) {
+      return Some(referenced);
+    }
+    self.images.iter().find(|image| {
+      image.game == game_name
+        && image.hash == referenced.hash
+        && image.is_extracted(&self.library_dir)
+    })
+  }
+
+  pub fn resolve_extracted_path(&self, game_name: &str, reference: &str) -> Option<PathBuf> {
+    self
+      .resolve(game_name, reference)
+      .map(|image| image.extracted_path(&self.library_dir))
+  }
+
+  pub fn is_extracted_source(&self, game_name: &str, source: &Path) -> bool {
+    self
+      .find_by_source(game_name, source)
+      .is_some_and(|image| image.is_extracted(&self.library_dir))
+  }
+
+  // The hash of the image, re-using the previously computed hash when the source hasn't changed on
+  // disk.  Hashing a multi-GB image takes a while, so it runs on the blocking thread pool.
+  //
+  // `content` is what actually gets hashed, this differs from `source` when the user's image
+  // had to be converted before extraction (ie. BIN/CUE)
+  pub async fn image_hash(
+    &self,
+    game_name: &str,
+    source: &Path,
+    content: &Path,
+  ) -> Result<String, IsoLibraryError> {
+    if let Some(image) = self.find_by_source(game_name, source) {
+      return Ok(image.hash.clone());
+    }
+    log::info!("Hashing ISO source {}", content.display());
+    let content = content.to_path_buf();
+    tokio::task::spawn_blocking(move || hash_image_source(&content))
+      .await
+      .map_err(|err| IsoLibraryError::Library(format!("Unable to hash the ISO: {err}")))?
+  }
+
+  // Works out where the image with the given hash should be extracted to, and whether it has
+  // already been extracted (in which case extraction can be skipped)
+  pub fn prepare_extraction(&self, game_name: &str, hash: String) -> PreparedImage {
+    let already_extracted = self
+      .find_by_hash(game_name, &hash)
+      .is_some_and(|image| image.is_extracted(&self.library_dir));
+    let id = id_for_hash(&hash);
+    PreparedImage {
+      extraction_dir: self.library_dir.join(&id),
+      id,
+      hash,
+      already_extracted,
+    }
+  }
+
+  // Records a newly extracted image (or refreshes an existing one with the same hash)
+  pub fn register(
+    &mut self,
+    game_name: &str,
+    source: &Path,
+    hash: &str,
+  ) -> Result<IsoLibraryEntry, IsoLibraryError> {
+    let (source_size, source_modified) = source_fingerprint(source);
+    let id = id_for_hash(hash);
+    let build_info_path = self
+      .library_dir
+      .join(&id)
+      .join(game_name)
+      .join("buildinfo.json");
+    let serial = fs::read_to_string(&build_info_path)
+      .ok()
+      .and_then(|content| serde_json::from_str::<IsoBuildInfo>(&content).ok())
+      .and_then(|info| info.serial);
+    let region = serial.as_deref().and_then(region_from_serial);
+
+    let entry = match self.images.iter_mut().find(|image| image.id == id) {
+      Some(existing) => {
+        existing.source_path = source.to_string_lossy().into_owned();
+        existing.source_size = source_size;
+        existing.source_modified = source_modified;
+        if serial.is_some() {
+          existing.serial = serial;
+          existing.region = region;
+        }
+        existing.clone()
+      }
+      None => {
+        let entry = IsoLibraryEntry {
+          id,
+          game: game_name.to_owned(),
+          region,
+          serial,
+          hash: hash.to_owned(),
+          source_path: source.to_string_lossy().into_owned(),
+          source_size,
+          source_modified,
+          registered_date: chrono::Utc::now().to_rfc3339(),
+          references: vec![],
+        };
+        self.images.push(entry.clone());
+        entry
+      }
+    };
+    self.save()?;
+    Ok(entry)
+  }
+
+  // Points the given install at the image, releasing whichever image it used previously
+  pub fn add_reference(&mut self, id: &str, reference: &str) -> Result<(), IsoLibraryError> {
+    let game_name = match self.get(id) {
+      Some(image) => image.game.clone(),
+      None => {
+        return Err(IsoLibraryError::Library(format!(
+          "ISO '{id}' is not registered in the library"
+        )))
+      }
+    };
+    let previous: Vec<String> = self
+      .images
+      .iter()
+      .filter(|image| {
+        image.id != id && image.game == game_name && image.references.iter().any(|r| r == reference)
+      })
+      .map(|image| image.id.clone())
+      .collect();
+    for previous_id in previous {
+      self.release_from(&previous_id, reference)?;
+    }
+    if let Some(image) = self.images.iter_mut().find(|image| image.id == id) {
+      if !image.references.iter().any(|r| r == reference) {
+        image.references.push(reference.to_owned());
+      }
+    }
+    self.save()
+  }
+
+  // Drops the install's reference for the game, deleting extracted data nothing else uses
+  pub fn release(&mut self, game_name: &str, reference: &str) -> Result<(), IsoLibraryError> {
+    let ids: Vec<String> = self
+      .images
+      .iter()
+      .filter(|image| image.game == game_name && image.references.iter().any(|r| r == reference))
+      .map(|image| image.id.clone())
+      .collect();
+    for id in ids {
+      self.release_from(&id, reference)?;
+    }
+    self.save()
+  }
+
+  fn release_from(&mut self, id: &str, reference: &str) -> Result<(), IsoLibraryError> {
+    let library_dir = self.library_dir.clone();
+    if let Some(image) = self.images.iter_mut().find(|image| image.id == id) {
+      image.references.retain(|r| r != reference);
+      if image.references.is_empty() {
+        log::info!(
+          "ISO '{}' is no longer referenced, removing extracted data",
+          image.id
+        );
+        delete_dir(image.extraction_dir(&library_dir))?;
+      }
+    }
+    Ok(())
+  }
+
+  // Forgets an image entirely, only allowed once no install references it
+  pub fn unregister(&mut self, id: &str) -> Result<(), IsoLibraryError> {
+    let image = match self.get(id) {
+      Some(image) => image.clone(),
+      None => return Ok(()),
+    };
+    if !image.references.is_empty() {
+      return Err(IsoLibraryError::Library(format!(
+        "ISO '{}' is still in use by: {}",
+        id,
+        image.references.join(", ")
+      )));
+    }
+    delete_dir(image.extraction_dir(&self.library_dir))?;
+    self.images.retain(|image| image.id != id);
+    self.save()
+  }
+}
@@ -14,8 +14,21 @@ use native_dialog::{DialogBuilder, MessageLevel};
 use std::io::Write;
 
 mod cache;
+mod cli;
 mod commands;
+mod compile_cache;
 mod config;
+mod crash_reports;
+mod image_cache;
+mod iso_library;
+mod mod_dependencies;
+mod mod_packaging;
+mod mod_search;
+mod mod_source_generator;
+mod mod_versions;
+mod process_registry;
+mod repl;
+mod session_history;
 mod util;
 
 fn log_crash(panic_info: Option<&std::panic::PanicHookInfo>, error: Option<tauri::Error>) {
@@ -69,6 +82,22 @@ fn main() {
   // the panic_hook will log to a file in the folder of the executable
   std::panic::set_hook(Box::new(panic_hook));
 
+  // A subcommand runs the launcher headless, without creating any windows
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let cli_invocation = match cli::parse_args(&args) {
+    None => None,
+    Some(Ok(invocation)) => Some(invocation),
+    Some(Err(err)) => {
+      cli::print_usage_error(&err);
+      std::process::exit(cli::EXIT_USAGE);
+    }
+  };
+  let headless = cli_invocation.is_some();
+  let mut context = tauri::generate_context!();
+  if headless {
+    context.config_mut().app.windows.clear();
+  }
+
   let tauri_setup = tauri::Builder::default()
     .plugin(tauri_plugin_updater::Builder::new().build())
     .plugin(tauri_plugin_process::init())
@@ -80,7 +109,7 @@ fn main() {
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_shell::init())
     .plugin(tauri_plugin_fs::init())
-    .setup(|app| {
+    .setup(move |app| {
       let _ = TAURI_APP.set(app.app_handle().clone());
 
       // Setup Logging
@@ -125,25 +154,18 @@ fn main() {
         // - and per-module overrides
         // .level_for("opengoal-launcher", log::LevelFilter::Debug)
         // Output to stdout, files, and other Dispatch configurations
-        .chain(std::io::stdout())
+        // (stderr when headless, stdout is reserved for the command's JSON output)
+        .chain(if headless {
+          fern::Output::from(std::io::stderr())
+        } else {
+          fern::Output::from(std::io::stdout())
+        })
         .chain(fern::DateBased::new(&log_path, "/%Y-%m-%d.log"))
         // Apply globally
         .apply();
       match log_setup_ok {
         Ok(_) => {
           log::info!("Logging Initialized");
-          // Truncate rotated log files to '5'
-          let mut paths: Vec<_> = std::fs::read_dir(&log_path)?.map(|r| r.unwrap()).collect();
-          paths.sort_by_key(|dir| dir.path());
-          paths.reverse();
-          let mut i = 0;
-          for path in paths {
-            i += 1;
-            if i > 5 {
-              log::info!("deleting - {}", path.path().display());
-              std::fs::remove_file(path.path())?;
-            }
-          }
         }
         Err(err) => log::error!("Could not initialize logging {:?}", err),
       };
@@ -155,12 +177,34 @@ fn main() {
       //
       // This allows us to avoid hacky globals, and pass around information (in this case, the config)
       // to the relevant places
-      let config = tokio::sync::Mutex::new(config::LauncherConfig::load_config(
-        app.path().app_config_dir().ok(),
-      ));
-      app.manage(config);
+      let config = config::LauncherConfig::load_config(app.path().app_config_dir().ok());
+      // Truncate rotated log files to the configured retention count
+      if let Err(err) = util::process::prune_log_dir(&log_path, config.log_retention_count) {
+        log::warn!("Unable to prune old logs: {}", err);
+      }
+      app.manage(tokio::sync::Mutex::new(config));
       let cache = tokio::sync::Mutex::new(cache::LauncherCache::default());
       app.manage(cache);
+      let process_registry = tokio::sync::Mutex::new(process_registry::ProcessRegistry::default());
+      app.manage(process_registry);
+      let repl = tokio::sync::Mutex::new(repl::ReplManager::default());
+      app.manage(repl);
+      let image_cache = tokio::sync::Mutex::new(image_cache::ImageCache::load(
+        app
+          .path()
+          .app_cache_dir()
+          .ok()
+          .map(|dir| dir.join("mod-art")),
+      ));
+      app.manage(image_cache);
+
+      if let Some(invocation) = cli_invocation {
+        let app_handle = app.app_handle().clone();
+        tauri::async_runtime::spawn(async move {
+          let exit_code = cli::run(&app_handle, invocation).await;
+          std::process::exit(exit_code);
+        });
+      }
       Ok(())
     })
     .invoke_handler(tauri::generate_handler![
@@ -170,48 +214,81 @@ fn main() {
       commands::binaries::open_repl,
       commands::binaries::run_compiler,
       commands::binaries::run_decompiler,
+      commands::binaries::start_repl,
       commands::binaries::update_data_directory,
+      commands::cache::get_cached_mod_art,
       commands::cache::get_mod_sources_data,
       commands::cache::refresh_mod_sources,
+      commands::cache::search_cached_mods,
       commands::config::cleanup_enabled_texture_packs,
+      commands::config::delete_launch_profile,
       commands::config::does_active_tooling_version_meet_minimum,
       commands::config::does_active_tooling_version_support_game,
+      commands::config::get_launch_profiles,
       commands::config::get_setting_value,
       commands::config::is_avx_requirement_met,
       commands::config::is_opengl_requirement_met,
       commands::config::reset_to_defaults,
+      commands::config::save_launch_profile,
       commands::config::set_install_directory,
       commands::config::update_mods_setting_value,
       commands::config::update_setting_value,
       commands::download::download_file,
       commands::features::mods::base_game_iso_exists,
+      commands::features::mods::check_for_mod_updates,
       commands::features::mods::compile_for_mod_install,
       commands::features::mods::decompile_for_mod_install,
       commands::features::mods::download_and_extract_new_mod,
       commands::features::mods::extract_iso_for_mod_install,
       commands::features::mods::extract_new_mod,
+      commands::features::mods::generate_mod_source,
       commands::features::mods::get_launch_mod_string,
       commands::features::mods::get_local_mod_cover_base64,
       commands::features::mods::get_local_mod_thumbnail_base64,
+      commands::features::mods::get_previous_mod_versions,
+      commands::features::mods::install_mod_with_dependencies,
       commands::features::mods::launch_mod,
+      commands::features::mods::list_local_mods,
       commands::features::mods::open_repl_for_mod,
+      commands::features::mods::package_local_mod,
       commands::features::mods::reset_mod_settings,
+      commands::features::mods::resolve_mod_install_plan,
+      commands::features::mods::rollback_mod_install,
       commands::features::mods::save_mod_install_info,
+      commands::features::mods::start_repl_for_mod,
+      commands::features::mods::switch_mod_version,
       commands::features::mods::uninstall_mod,
+      commands::features::mods::update_mod,
       commands::features::texture_packs::delete_texture_packs,
       commands::features::texture_packs::extract_new_texture_pack,
       commands::features::texture_packs::list_extracted_texture_pack_info,
       commands::features::texture_packs::update_texture_pack_data,
       commands::game::get_furthest_game_milestone,
+      commands::game::get_crash_reports,
+      commands::game::get_play_session_summary,
+      commands::game::get_play_sessions,
+      commands::game::is_game_running,
+      commands::game::kill_game,
+      commands::game::list_running_games,
       commands::game::reset_game_settings,
+      commands::game::stop_game,
       commands::game::uninstall_game,
+      commands::iso::link_known_iso,
+      commands::iso::list_iso_library,
+      commands::iso::remove_iso_from_library,
       commands::logging::frontend_log,
+      commands::repl::close_repl,
+      commands::repl::connect_repl,
+      commands::repl::list_repl_sessions,
+      commands::repl::send_repl_form,
       commands::support::generate_support_package,
       commands::util::delete_old_data_directory,
+      commands::util::get_resolved_terminal,
       commands::util::has_old_data_directory,
       commands::util::is_diskspace_requirement_met,
       commands::util::is_macos_version_15_or_above,
       commands::util::is_minimum_vcc_runtime_installed,
+      commands::util::list_available_terminals,
       commands::util::path_exists,
       commands::versions::download_version,
       commands::versions::ensure_active_version_still_exists,
@@ -221,7 +298,7 @@ fn main() {
       commands::window::open_dir_in_os,
       commands::window::open_main_window,
     ])
-    .build(tauri::generate_context!())
+    .build(context)
     .map_err(|err| {
       log_crash(None, Some(err));
     });
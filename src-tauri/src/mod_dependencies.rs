@@ -0,0 +1,267 @@
+// Resolves the mods a mod depends on into an install plan
+//
+// Dependencies are declared per version in the mod sources, as a mod id, an optional source name
+// (the depending mod's own source otherwise) and an optional semver requirement.  The plan lists
+// every mod that needs installing with dependencies ahead of the mods that need them, anything
+// already installed at a satisfying version is left alone.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::cache::{LauncherCache, ModDependency, ModInfo, ModVersion};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModDependencyError {
+  #[error("{0}")]
+  Cycle(String),
+  #[error("{0}")]
+  Unresolved(String),
+  #[error("{0}")]
+  InvalidRequirement(String),
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedModInstall {
+  pub source_name: String,
+  pub mod_name: String,
+  pub version: String,
+  pub download_url: String,
+  pub dependencies: Vec<ModDependency>,
+}
+
+// Mod versions are usually tagged like "v1.2" rather than being strict semver
+pub fn parse_mod_version(version: &str) -> Option<semver::Version> {
+  let version = version.trim().trim_start_matches(['v', 'V']);
+  if let Ok(parsed) = semver::Version::parse(version) {
+    return Some(parsed);
+  }
+  let mut parts: Vec<&str> = version.split('.').collect();
+  if parts.is_empty() || parts.len() > 3 {
+    return None;
+  }
+  while parts.len() < 3 {
+    parts.push("0");
+  }
+  semver::Version::parse(&parts.join(".")).ok()
+}
+
+// Versions are stored without the leading `v` release tags usually have, so `v1.2` and `1.2` are
+// the same version everywhere
+pub fn normalize_mod_version(version: &str) -> &str {
+  let version = version.trim();
+  match version.strip_prefix(['v', 'V']) {
+    Some(stripped) if stripped.starts_with(|c: char| c.is_ascii_digit()) => stripped,
+    _ => version,
+  }
+}
+
+// Whether `candidate` is strictly newer than the installed version.  Versions are compared by
+// number when both parse, otherwise by when they were published, which needs the installed version
+// to still be listed in the mod source.
+pub fn is_newer_version(
+  mod_info: &ModInfo,
+  candidate: &ModVersion,
+  installed_version: &str,
+) -> bool {
+  if let (Some(candidate), Some(installed)) = (
+    parse_mod_version(&candidate.version),
+    parse_mod_version(installed_version),
+  ) {
+    return candidate > installed;
+  }
+  let installed_published = mod_info
+    .versions
+    .iter()
+    .find(|version| version.version == installed_version)
+    .and_then(|version| version.published());
+  match (candidate.published(), installed_published) {
+    (Some(candidate), Some(installed)) => candidate > installed,
+    _ => false,
+  }
+}
+
+pub fn satisfies(requirement: Option<&str>, version: &str) -> Result<bool, ModDependencyError> {
+  let requirement = match requirement {
+    Some(requirement) => requirement,
+    None => return Ok(true),
+  };
+  let requirement = semver::VersionReq::parse(requirement).map_err(|err| {
+    ModDependencyError::InvalidRequirement(format!(
+      "Invalid version requirement '{requirement}': {err}"
+    ))
+  })?;
+  Ok(parse_mod_version(version).is_some_and(|version| requirement.matches(&version)))
+}
+
+// Fills in the source of dependencies that don't name one
+pub fn with_sources(source_name: &str, dependencies: &[ModDependency]) -> Vec<ModDependency> {
+  dependencies
+    .iter()
+    .map(|dependency| ModDependency {
+      source: Some(
+        dependency
+          .source
+          .clone()
+          .unwrap_or_else(|| source_name.to_owned()),
+      ),
+      ..dependency.clone()
+    })
+    .collect()
+}
+
+// Installed mods (as "source/mod") whose recorded dependencies include the given mod
+pub fn dependents(
+  installed_dependencies: &HashMap<String, HashMap<String, Vec<ModDependency>>>,
+  source_name: &str,
+  mod_name: &str,
+) -> Vec<String> {
+  let mut dependents: Vec<String> = installed_dependencies
+    .iter()
+    .flat_map(|(dependent_source, mods)| {
+      mods
+        .iter()
+        .filter(|(_, dependencies)| {
+          dependencies.iter().any(|dependency| {
+            dependency.id == mod_name && dependency.source.as_deref() == Some(source_name)
+          })
+        })
+        .map(move |(dependent_mod, _)| format!("{dependent_source}/{dependent_mod}"))
+    })
+    .collect();
+  dependents.sort();
+  dependents
+}
+
+struct Resolver<'a> {
+  cache: &'a LauncherCache,
+  // source -> mod -> version
+  installed: &'a HashMap<String, HashMap<String, String>>,
+  game_name: &'a str,
+  visiting: Vec<String>,
+  plan: Vec<PlannedModInstall>,
+}
+
+impl Resolver<'_> {
+  fn pick_version(
+    &self,
+    source_name: &str,
+    mod_name: &str,
+    requirement: Option<&str>,
+    exact_version: Option<&str>,
+  ) -> Result<ModVersion, ModDependencyError> {
+    let mod_info = self.cache.find_mod(source_name, mod_name).ok_or_else(|| {
+      ModDependencyError::Unresolved(format!(
+        "{mod_name} could not be found in the {source_name} mod source"
+      ))
+    })?;
+    // the highest matching version wins, versions are listed by when they were published so a
+    // backport can come before a newer major version
+    let mut picked: Option<&ModVersion> = None;
+    for version in mod_info.compatible_versions(self.game_name, std::env::consts::OS) {
+      let matches_exact = exact_version.is_none_or(|exact| exact == version.version);
+      if !matches_exact || !satisfies(requirement, &version.version)? {
+        continue;
+      }
+      if picked.is_none_or(|picked| is_newer_version(mod_info, version, &picked.version)) {
+        picked = Some(version);
+      }
+    }
+    picked.cloned().ok_or_else(|| {
+      ModDependencyError::Unresolved(format!(
+        "No compatible version of {mod_name} from {source_name} matches {}",
+        exact_version.or(requirement).unwrap_or("any version")
+      ))
+    })
+  }
+
+  fn visit(
+    &mut self,
+    source_name: &str,
+    mod_name: &str,
+    requirement: Option<&str>,
+    exact_version: Option<&str>,
+    is_root: bool,
+  ) -> Result<(), ModDependencyError> {
+    let key = format!("{source_name}/{mod_name}");
+    if let Some(planned) = self
+      .plan
+      .iter()
+      .find(|planned| planned.source_name == source_name && planned.mod_name == mod_name)
+    {
+      if satisfies(requirement, &planned.version)? {
+        return Ok(());
+      }
+      return Err(ModDependencyError::Unresolved(format!(
+        "{key} is required at {} but version {} was already picked",
+        requirement.unwrap_or("any version"),
+        planned.version
+      )));
+    }
+    if self.visiting.contains(&key) {
+      return Err(ModDependencyError::Cycle(format!(
+        "Circular mod dependency: {} -> {key}",
+        self.visiting.join(" -> ")
+      )));
+    }
+    if !is_root {
+      let installed_version = self
+        .installed
+        .get(source_name)
+        .and_then(|mods| mods.get(mod_name));
+      if let Some(installed_version) = installed_version {
+        if satisfies(requirement, installed_version)? {
+          return Ok(());
+        }
+      }
+    }
+
+    let version = self.pick_version(source_name, mod_name, requirement, exact_version)?;
+    let dependencies = with_sources(source_name, &version.dependencies);
+    self.visiting.push(key);
+    for dependency in &dependencies {
+      self.visit(
+        dependency.source.as_deref().unwrap_or(source_name),
+        &dependency.id,
+        dependency.version.as_deref(),
+        None,
+        false,
+      )?;
+    }
+    self.visiting.pop();
+    let download_url = version
+      .asset_url(std::env::consts::OS)
+      .cloned()
+      .unwrap_or_default();
+    self.plan.push(PlannedModInstall {
+      source_name: source_name.to_owned(),
+      mod_name: mod_name.to_owned(),
+      version: version.version,
+      download_url,
+      dependencies,
+    });
+    Ok(())
+  }
+}
+
+// The mods to install, in order, for the requested mod to work.  The requested mod is always
+// last, and is the newest compatible version unless `version` is given.
+pub fn resolve_install_plan(
+  cache: &LauncherCache,
+  installed: &HashMap<String, HashMap<String, String>>,
+  game_name: &str,
+  source_name: &str,
+  mod_name: &str,
+  version: Option<&str>,
+) -> Result<Vec<PlannedModInstall>, ModDependencyError> {
+  let mut resolver = Resolver {
+    cache,
+    installed,
+    game_name,
+    visiting: Vec::new(),
+    plan: Vec::new(),
+  };
+  resolver.visit(source_name, mod_name, None, version, true)?;
+  Ok(resolver.plan)
+}
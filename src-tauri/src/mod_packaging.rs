@@ -0,0 +1,468 @@
+// Packages a mod from the `_local` source into archives that can be published in a mod source
+//
+// The mod's folder is checked for the tooling and game code a mod needs, then an archive is
+// written for every requested platform (zips for windows, tarballs everywhere else) alongside a
+// `SHA256SUMS` file.  Each archive carries a `mod-manifest.json` describing the mod, and the
+// result includes a `ModInfo` entry for the packaged version that can be pasted into a mod
+// source as is.
+//
+// The same manifest is read back when a mod archive is imported into the `_local` source, where it
+// names the mod's folder and records the version that was imported.
+
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Component, Path, PathBuf},
+  str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::{
+  cache::{ModDependency, ModInfo, ModVersion, ModVersionSettings},
+  config::SupportedGame,
+  mod_dependencies::normalize_mod_version,
+  mod_versions::PREVIOUS_VERSIONS_DIR,
+  util::{
+    archive::{create_archive, ArchiveError, ArchiveFile, ArchiveFormat},
+    file::{create_dir, hash_file_contents},
+  },
+};
+
+pub const MANIFEST_FILE_NAME: &str = "mod-manifest.json";
+const CHECKSUMS_FILE_NAME: &str = "SHA256SUMS";
+const PLATFORMS: [&str; 3] = ["windows", "linux", "macos"];
+const TOOLING: [&str; 3] = ["gk", "goalc", "extractor"];
+// created while installing or playing the mod, and the extracted game files can't be
+// redistributed anyway
+const EXCLUDED_DATA_DIRS: [&str; 4] = ["iso_data", "decompiler_out", "out", "log"];
+pub const COVER_FILE_NAME: &str = "cover.png";
+pub const THUMBNAIL_FILE_NAME: &str = "thumbnail.png";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModPackagingError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  Archive(#[from] ArchiveError),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error("{0}")]
+  Validation(String),
+}
+
+// Describes a packaged mod, stored at the root of its archives
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModManifest {
+  pub id: String,
+  pub display_name: String,
+  pub version: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub authors: Vec<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub supported_games: Vec<String>,
+  // relative to the root of the archive
+  pub cover: Option<String>,
+  pub thumbnail: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModPackageOptions {
+  pub version: String,
+  pub display_name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub authors: Vec<String>,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  // the game the mod is installed for when empty
+  #[serde(default)]
+  pub supported_games: Vec<String>,
+  // the current platform when empty
+  #[serde(default)]
+  pub platforms: Vec<String>,
+  #[serde(default)]
+  pub dependencies: Vec<ModDependency>,
+  #[serde(default)]
+  pub settings: ModVersionSettings,
+  pub website_url: Option<String>,
+  // where the archives will be uploaded, used to fill in the asset urls
+  pub asset_base_url: Option<String>,
+  pub output_dir: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PackagedArchive {
+  pub platform: String,
+  pub path: String,
+  pub file_name: String,
+  pub size: u64,
+  pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModPackage {
+  pub output_dir: String,
+  pub manifest: ModManifest,
+  pub archives: Vec<PackagedArchive>,
+  pub mod_info: ModInfo,
+  // `"<id>": { ...mod_info }`, ready to paste into the `mods` of a mod source
+  pub source_entry: String,
+}
+
+fn tooling_file_name(executable: &str, platform: &str) -> String {
+  if platform == "windows" {
+    return format!("{executable}.exe");
+  }
+  executable.to_owned()
+}
+
+fn archive_format_for(platform: &str) -> ArchiveFormat {
+  if platform == "windows" {
+    return ArchiveFormat::Zip;
+  }
+  ArchiveFormat::TarGz
+}
+
+fn validate(
+  mod_dir: &Path,
+  options: &ModPackageOptions,
+  platforms: &[String],
+  supported_games: &[String],
+) -> Result<(), ModPackagingError> {
+  let mut problems = Vec::new();
+  if !mod_dir.is_dir() {
+    return Err(ModPackagingError::Validation(format!(
+      "{} is not a folder",
+      mod_dir.display()
+    )));
+  }
+  if options.version.trim().is_empty() {
+    problems.push("a version is required".to_owned());
+  }
+  if options.display_name.trim().is_empty() {
+    problems.push("a display name is required".to_owned());
+  }
+  for game in supported_games {
+    if SupportedGame::from_str(game).is_err() {
+      problems.push(format!("'{game}' is not a supported game"));
+    }
+  }
+  for platform in platforms {
+    if !PLATFORMS.contains(&platform.as_str()) {
+      problems.push(format!("'{platform}' is not a known platform"));
+      continue;
+    }
+    for executable in TOOLING {
+      let file_name = tooling_file_name(executable, platform);
+      if !mod_dir.join(&file_name).is_file() {
+        problems.push(format!(
+          "{file_name} is missing, it's required for {platform}"
+        ));
+      }
+    }
+  }
+  if !mod_dir.join("data").join("goal_src").is_dir() {
+    problems.push("data/goal_src is missing".to_owned());
+  }
+  if problems.is_empty() {
+    return Ok(());
+  }
+  Err(ModPackagingError::Validation(format!(
+    "The mod can't be packaged: {}",
+    problems.join(", ")
+  )))
+}
+
+// Everything from the mod's folder that belongs in the archive for `platform`
+fn collect_files(
+  mod_dir: &Path,
+  output_dir: &Path,
+  platform: &str,
+) -> Result<Vec<ArchiveFile>, ModPackagingError> {
+  let platform_tooling: Vec<String> = TOOLING
+    .iter()
+    .map(|executable| tooling_file_name(executable, platform))
+    .collect();
+  let other_tooling: Vec<String> = PLATFORMS
+    .iter()
+    .flat_map(|other| TOOLING.iter().map(|exe| tooling_file_name(exe, other)))
+    .filter(|file_name| !platform_tooling.contains(file_name))
+    .collect();
+  // previous versions of an installed mod are kept beside it, they are never part of the mod
+  let excluded_dirs: Vec<PathBuf> = EXCLUDED_DATA_DIRS
+    .iter()
+    .map(|dir| mod_dir.join("data").join(dir))
+    .chain(std::iter::once(mod_dir.join(PREVIOUS_VERSIONS_DIR)))
+    .collect();
+
+  let mut files = Vec::new();
+  let walker = WalkDir::new(mod_dir)
+    .sort_by_file_name()
+    .into_iter()
+    .filter_entry(|entry| {
+      entry.path() != output_dir && !excluded_dirs.iter().any(|dir| entry.path() == dir)
+    });
+  for entry in walker {
+    let entry = entry.map_err(std::io::Error::from)?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+    let relative_path = entry.path().strip_prefix(mod_dir).unwrap_or(entry.path());
+    let name = relative_path
+      .components()
+      .map(|component| component.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/");
+    if name == MANIFEST_FILE_NAME || other_tooling.contains(&name) {
+      continue;
+    }
+    files.push(ArchiveFile {
+      source: entry.path().to_path_buf(),
+      executable: platform_tooling.contains(&name),
+      name,
+    });
+  }
+  Ok(files)
+}
+
+fn sha256_of(path: &Path) -> Result<String, ModPackagingError> {
+  let mut hasher = Sha256::new();
+  hash_file_contents(&mut hasher, path)?;
+  Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn asset_url(base_url: &Option<String>, file_name: &str) -> String {
+  match base_url {
+    Some(base_url) => format!("{}/{file_name}", base_url.trim_end_matches('/')),
+    // the author fills in where the file ends up being hosted
+    None => file_name.to_owned(),
+  }
+}
+
+pub fn package_mod(
+  mod_dir: &Path,
+  default_output_dir: &Path,
+  game_name: &str,
+  mod_name: &str,
+  options: &ModPackageOptions,
+) -> Result<ModPackage, ModPackagingError> {
+  let platforms = if options.platforms.is_empty() {
+    vec![std::env::consts::OS.to_owned()]
+  } else {
+    options.platforms.clone()
+  };
+  let supported_games = if options.supported_games.is_empty() {
+    vec![game_name.to_owned()]
+  } else {
+    options.supported_games.clone()
+  };
+  validate(mod_dir, options, &platforms, &supported_games)?;
+
+  let output_dir = options
+    .output_dir
+    .as_ref()
+    .map(PathBuf::from)
+    .unwrap_or_else(|| default_output_dir.to_path_buf());
+  create_dir(&output_dir)?;
+
+  let version = normalize_mod_version(&options.version);
+  let manifest = ModManifest {
+    id: mod_name.to_owned(),
+    display_name: options.display_name.clone(),
+    version: version.to_owned(),
+    description: options.description.clone(),
+    authors: options.authors.clone(),
+    tags: options.tags.clone(),
+    supported_games: supported_games.clone(),
+    cover: mod_dir
+      .join(COVER_FILE_NAME)
+      .is_file()
+      .then(|| COVER_FILE_NAME.to_owned()),
+    thumbnail: mod_dir
+      .join(THUMBNAIL_FILE_NAME)
+      .is_file()
+      .then(|| THUMBNAIL_FILE_NAME.to_owned()),
+  };
+  let manifest_path = output_dir.join(MANIFEST_FILE_NAME);
+  fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+
+  let mut archives = Vec::new();
+  let mut assets: HashMap<String, Option<String>> = PLATFORMS
+    .iter()
+    .map(|platform| (platform.to_string(), None))
+    .collect();
+  for platform in &platforms {
+    let format = archive_format_for(platform);
+    let file_name = format!("{mod_name}-{platform}-v{version}.{}", format.extension());
+    let archive_path = output_dir.join(&file_name);
+    let mut files = collect_files(mod_dir, &output_dir, platform)?;
+    files.push(ArchiveFile {
+      source: manifest_path.clone(),
+      name: MANIFEST_FILE_NAME.to_owned(),
+      executable: false,
+    });
+    create_archive(&archive_path, format, &files)?;
+    assets.insert(
+      platform.clone(),
+      Some(asset_url(&options.asset_base_url, &file_name)),
+    );
+    archives.push(PackagedArchive {
+      platform: platform.clone(),
+      path: archive_path.to_string_lossy().into_owned(),
+      size: archive_path.metadata()?.len(),
+      sha256: sha256_of(&archive_path)?,
+      file_name,
+    });
+  }
+  let checksums: String = archives
+    .iter()
+    .map(|archive| format!("{}  {}\n", archive.sha256, archive.file_name))
+    .collect();
+  fs::write(output_dir.join(CHECKSUMS_FILE_NAME), checksums)?;
+
+  // the art is uploaded alongside the archives
+  let art_url = |file_name: &Option<String>| -> Result<Option<String>, ModPackagingError> {
+    let file_name = match file_name {
+      Some(file_name) => file_name,
+      None => return Ok(None),
+    };
+    let published_name = format!("{mod_name}-{file_name}");
+    fs::copy(mod_dir.join(file_name), output_dir.join(&published_name))?;
+    Ok(Some(asset_url(&options.asset_base_url, &published_name)))
+  };
+  let cover_art_url = art_url(&manifest.cover)?;
+  let thumbnail_art_url = art_url(&manifest.thumbnail)?;
+
+  let mod_info = ModInfo {
+    display_name: manifest.display_name.clone(),
+    description: manifest.description.clone(),
+    authors: manifest.authors.clone(),
+    tags: manifest.tags.clone(),
+    supported_games: supported_games.clone(),
+    website_url: options.website_url.clone(),
+    versions: vec![ModVersion {
+      version: manifest.version.clone(),
+      published_date: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+      assets,
+      supported_games: Some(supported_games),
+      dependencies: options.dependencies.clone(),
+      settings: options.settings.clone(),
+    }],
+    per_game_config: None,
+    cover_art_url,
+    thumbnail_art_url,
+    external_link: None,
+  };
+  let source_entry = format!(
+    "{}: {}",
+    serde_json::to_string(mod_name)?,
+    serde_json::to_string_pretty(&mod_info)?
+  );
+  fs::write(output_dir.join("mod-source-entry.json"), &source_entry)?;
+  log::info!(
+    "Packaged {} v{} into {}",
+    mod_name,
+    version,
+    output_dir.display()
+  );
+
+  Ok(ModPackage {
+    output_dir: output_dir.to_string_lossy().into_owned(),
+    manifest,
+    archives,
+    mod_info,
+    source_entry,
+  })
+}
+
+// Art paths in a manifest have to stay inside the mod's folder
+fn is_relative_to_mod(path: &str) -> bool {
+  Path::new(path)
+    .components()
+    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+// Checks a manifest from a mod archive that is being imported for `game_name`
+pub fn validate_manifest(manifest: &ModManifest, game_name: &str) -> Result<(), ModPackagingError> {
+  let mut problems = Vec::new();
+  let id = manifest.id.trim();
+  if id.is_empty()
+    || id.starts_with(['.', '_'])
+    || id.contains(['/', '\\'])
+    || Path::new(id).components().count() != 1
+  {
+    problems.push(format!("'{}' can't be used as a mod id", manifest.id));
+  }
+  if manifest.version.trim().is_empty() {
+    problems.push("a version is required".to_owned());
+  }
+  for game in &manifest.supported_games {
+    if SupportedGame::from_str(game).is_err() {
+      problems.push(format!("'{game}' is not a supported game"));
+    }
+  }
+  if !manifest.supported_games.is_empty()
+    && !manifest
+      .supported_games
+      .iter()
+      .any(|game| game == game_name)
+  {
+    problems.push(format!("the mod doesn't support {game_name}"));
+  }
+  for art in [&manifest.cover, &manifest.thumbnail].into_iter().flatten() {
+    if !is_relative_to_mod(art) {
+      problems.push(format!("'{art}' is not a path inside of the mod"));
+    }
+  }
+  if problems.is_empty() {
+    return Ok(());
+  }
+  Err(ModPackagingError::Validation(format!(
+    "The mod's manifest is invalid: {}",
+    problems.join(", ")
+  )))
+}
+
+// The manifest an imported mod was installed with, if it came with one
+pub fn read_installed_manifest(mod_dir: &Path) -> Option<ModManifest> {
+  let manifest_path = mod_dir.join(MANIFEST_FILE_NAME);
+  if !manifest_path.is_file() {
+    return None;
+  }
+  match fs::read_to_string(&manifest_path)
+    .map_err(ModPackagingError::from)
+    .and_then(|contents| Ok(serde_json::from_str(&contents)?))
+  {
+    Ok(manifest) => Some(manifest),
+    Err(err) => {
+      log::warn!("Unable to read {}: {}", manifest_path.display(), err);
+      None
+    }
+  }
+}
+
+// The art the manifest points to, or the conventionally named file when it doesn't name any
+pub fn mod_art_path(
+  mod_dir: &Path,
+  manifest_art: Option<&String>,
+  default: &str,
+) -> Option<PathBuf> {
+  let file_name = manifest_art
+    .filter(|art| is_relative_to_mod(art))
+    .map(String::as_str)
+    .unwrap_or(default);
+  let path = mod_dir.join(file_name);
+  path.is_file().then_some(path)
+}
@@ -0,0 +1,250 @@
+// Searches the mods of every cached mod source
+//
+// Mods are matched against free text (id, display name, description and authors, every word has
+// to appear somewhere), tags, the game they support, whether a version is available for a platform
+// and whether they are installed.  The matches are sorted and returned a page at a time so the
+// frontend doesn't have to receive every source just to show a few mods.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{LauncherCache, ModInfo, ModVersion};
+
+const DEFAULT_PAGE_SIZE: usize = 24;
+const MAX_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModSearchError {
+  #[error("{0}")]
+  InvalidQuery(String),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ModSearchSort {
+  // most recently released mods first
+  #[default]
+  Newest,
+  // mods with the most recently published version first
+  RecentlyUpdated,
+  Name,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModSearchQuery {
+  pub text: Option<String>,
+  // mods have to have every one of these tags
+  #[serde(default)]
+  pub tags: Vec<String>,
+  pub game: Option<String>,
+  // one of "windows", "linux" or "macos"
+  pub platform: Option<String>,
+  // requires `game`, mods are installed per game
+  pub installed: Option<bool>,
+  #[serde(default)]
+  pub sort: ModSearchSort,
+  // starts at 0
+  #[serde(default)]
+  pub page: usize,
+  #[serde(default = "default_page_size")]
+  pub page_size: usize,
+}
+
+fn default_page_size() -> usize {
+  DEFAULT_PAGE_SIZE
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModSearchEntry {
+  pub source_name: String,
+  pub mod_name: String,
+  pub mod_info: ModInfo,
+  pub installed_version: Option<String>,
+  // newest version for the query's game and platform, or of any version when they aren't given
+  pub latest_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModSearchResult {
+  pub mods: Vec<ModSearchEntry>,
+  // matches across every page
+  pub total: usize,
+  pub page: usize,
+  pub page_size: usize,
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+  haystack.to_lowercase().contains(needle)
+}
+
+fn matches_text(mod_name: &str, mod_info: &ModInfo, text: &str) -> bool {
+  text.split_whitespace().all(|term| {
+    let term = term.to_lowercase();
+    contains_ignore_case(mod_name, &term)
+      || contains_ignore_case(&mod_info.display_name, &term)
+      || contains_ignore_case(&mod_info.description, &term)
+      || mod_info
+        .authors
+        .iter()
+        .any(|author| contains_ignore_case(author, &term))
+  })
+}
+
+fn matches_tags(mod_info: &ModInfo, tags: &[String]) -> bool {
+  tags.iter().all(|tag| {
+    mod_info
+      .tags
+      .iter()
+      .any(|mod_tag| mod_tag.eq_ignore_ascii_case(tag))
+  })
+}
+
+fn is_available(version: &ModVersion, game: Option<&str>, platform: Option<&str>) -> bool {
+  game.is_none_or(|game| version.supports_game(game))
+    && platform.is_none_or(|platform| version.asset_url(platform).is_some())
+}
+
+fn supports_game(mod_info: &ModInfo, game: &str) -> bool {
+  mod_info
+    .supported_games
+    .iter()
+    .any(|supported| supported == game)
+    || mod_info
+      .versions
+      .iter()
+      .any(|version| version.supports_game(game))
+}
+
+// The newest version that can be installed, taking the game and platform into account
+fn latest_version<'a>(
+  mod_info: &'a ModInfo,
+  game: Option<&str>,
+  platform: Option<&str>,
+) -> Option<&'a ModVersion> {
+  mod_info
+    .versions
+    .iter()
+    .filter(|version| is_available(version, game, platform))
+    .max_by_key(|version| version.published())
+}
+
+// When the mod was first released, for the game when it has its own release date
+fn release_date(
+  mod_info: &ModInfo,
+  game: Option<&str>,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+  let per_game_release = game
+    .and_then(|game| mod_info.per_game_config.as_ref()?.get(game))
+    .and_then(|config| config.release_date.as_ref())
+    .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok());
+  per_game_release.or_else(|| {
+    mod_info
+      .versions
+      .iter()
+      .filter_map(|version| version.published())
+      .min()
+  })
+}
+
+struct SearchMatch<'a> {
+  source_name: &'a str,
+  mod_name: &'a str,
+  mod_info: &'a ModInfo,
+  installed_version: Option<&'a String>,
+}
+
+pub fn search_mods(
+  cache: &LauncherCache,
+  installed: &HashMap<String, HashMap<String, String>>,
+  query: &ModSearchQuery,
+) -> Result<ModSearchResult, ModSearchError> {
+  if query.installed.is_some() && query.game.is_none() {
+    return Err(ModSearchError::InvalidQuery(
+      "A game is required to filter by installed mods".to_owned(),
+    ));
+  }
+  let game = query.game.as_deref();
+  let platform = query.platform.as_deref();
+  let text = query
+    .text
+    .as_deref()
+    .map(str::trim)
+    .filter(|text| !text.is_empty());
+
+  let mut matches: Vec<SearchMatch> = Vec::new();
+  for source in cache.mod_sources.values() {
+    for (mod_name, mod_info) in &source.mods {
+      let installed_version = installed
+        .get(&source.source_name)
+        .and_then(|mods| mods.get(mod_name));
+      let is_match = text.is_none_or(|text| matches_text(mod_name, mod_info, text))
+        && matches_tags(mod_info, &query.tags)
+        && game.is_none_or(|game| supports_game(mod_info, game))
+        && (platform.is_none() || latest_version(mod_info, game, platform).is_some())
+        && query
+          .installed
+          .is_none_or(|installed| installed == installed_version.is_some());
+      if is_match {
+        matches.push(SearchMatch {
+          source_name: &source.source_name,
+          mod_name,
+          mod_info,
+          installed_version,
+        });
+      }
+    }
+  }
+
+  let by_name = |a: &SearchMatch, b: &SearchMatch| {
+    a.mod_info
+      .display_name
+      .to_lowercase()
+      .cmp(&b.mod_info.display_name.to_lowercase())
+      .then_with(|| (a.source_name, a.mod_name).cmp(&(b.source_name, b.mod_name)))
+  };
+  match query.sort {
+    ModSearchSort::Newest => matches.sort_by(|a, b| {
+      release_date(b.mod_info, game)
+        .cmp(&release_date(a.mod_info, game))
+        .then_with(|| by_name(a, b))
+    }),
+    ModSearchSort::RecentlyUpdated => matches.sort_by(|a, b| {
+      let updated = |mod_info: &ModInfo| {
+        latest_version(mod_info, game, platform).and_then(|version| version.published())
+      };
+      updated(b.mod_info)
+        .cmp(&updated(a.mod_info))
+        .then_with(|| by_name(a, b))
+    }),
+    ModSearchSort::Name => matches.sort_by(by_name),
+  }
+
+  let page_size = match query.page_size {
+    0 => DEFAULT_PAGE_SIZE,
+    page_size => page_size.min(MAX_PAGE_SIZE),
+  };
+  let total = matches.len();
+  let mods = matches
+    .into_iter()
+    .skip(query.page.saturating_mul(page_size))
+    .take(page_size)
+    .map(|found| ModSearchEntry {
+      source_name: found.source_name.to_owned(),
+      mod_name: found.mod_name.to_owned(),
+      installed_version: found.installed_version.cloned(),
+      latest_version: latest_version(found.mod_info, game, platform)
+        .map(|version| version.version.clone()),
+      mod_info: found.mod_info.clone(),
+    })
+    .collect();
+  Ok(ModSearchResult {
+    mods,
+    total,
+    page: query.page,
+    page_size,
+  })
+}
@@ -0,0 +1,389 @@
+// Generates a mod source JSON file from a folder of mod releases, so a source can be hosted from
+// any static file server
+//
+// Every sub-folder of the source folder is a mod, named after its id, holding its release
+// archives and a `mod.json` with the details shared by every version (the `mod-manifest.json`
+// written by `package_local_mod` works as well).  The platform and version of each archive are
+// taken from the end of its file name, `{id}-{platform}-v{version}` the way `package_local_mod`
+// names them (ie. `my-mod-windows-v1.2.0.zip`), and its published date from the file unless
+// `mod.json` says otherwise.  `cover.png` and `thumbnail.png` are used as the mod's
+// art when present.
+
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  path::{Path, PathBuf},
+  str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  cache::{
+    ModDependency, ModInfo, ModPerGameConfig, ModSourceData, ModVersion, ModVersionSettings,
+  },
+  config::SupportedGame,
+  mod_dependencies::{normalize_mod_version, parse_mod_version},
+  util::archive::{archive_name, detect_archive_format},
+};
+
+const SCHEMA_VERSION: &str = "1.0.0";
+const METADATA_FILE_NAMES: [&str; 2] = ["mod.json", "mod-manifest.json"];
+const PLATFORMS: [&str; 3] = ["windows", "linux", "macos"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModSourceGeneratorError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error("{0}")]
+  Validation(String),
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ModMetadata {
+  display_name: Option<String>,
+  #[serde(default)]
+  description: String,
+  #[serde(default)]
+  authors: Vec<String>,
+  #[serde(default)]
+  tags: Vec<String>,
+  #[serde(default)]
+  supported_games: Vec<String>,
+  website_url: Option<String>,
+  external_link: Option<String>,
+  cover_art_url: Option<String>,
+  thumbnail_art_url: Option<String>,
+  per_game_config: Option<HashMap<String, ModPerGameConfig>>,
+  // details that differ between versions, keyed by version
+  #[serde(default)]
+  versions: HashMap<String, VersionMetadata>,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VersionMetadata {
+  published_date: Option<String>,
+  supported_games: Option<Vec<String>>,
+  #[serde(default)]
+  dependencies: Vec<ModDependency>,
+  #[serde(default)]
+  settings: ModVersionSettings,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedModSource {
+  pub output_path: String,
+  pub mod_count: usize,
+  pub version_count: usize,
+  // files and mods that were skipped, and anything that had to be guessed
+  pub warnings: Vec<String>,
+}
+
+fn platform_from_token(token: &str) -> Option<&'static str> {
+  match token.to_lowercase().as_str() {
+    "windows" | "win" | "win64" => Some("windows"),
+    "linux" => Some("linux"),
+    "macos" | "mac" | "osx" | "darwin" => Some("macos"),
+    _ => None,
+  }
+}
+
+// The platform and version at the end of an archive's name, `{id}-{platform}-v{version}`.  It's
+// read from the end since versions (`v1.2.0-rc1`) and ids (`linux-tools`) can contain separators
+// and platform names themselves.
+fn parse_release_name(name: &str) -> Option<(&'static str, String)> {
+  name
+    .char_indices()
+    .rev()
+    .filter(|(_, c)| *c == '-' || *c == '_')
+    .find_map(|(index, _)| {
+      let version = normalize_mod_version(&name[index + 1..]);
+      if !version.starts_with(|c: char| c.is_ascii_digit()) || parse_mod_version(version).is_none()
+      {
+        return None;
+      }
+      let platform_token = name[..index].rsplit(['-', '_']).next()?;
+      let platform = platform_from_token(platform_token)?;
+      Some((platform, version.to_owned()))
+    })
+}
+
+fn file_url(base_url: &str, mod_id: &str, file_name: &str) -> String {
+  format!(
+    "{}/{}/{}",
+    base_url.trim_end_matches('/'),
+    mod_id.replace(' ', "%20"),
+    file_name.replace(' ', "%20")
+  )
+}
+
+fn modified_date(path: &Path) -> Option<chrono::DateTime<chrono::Utc>> {
+  fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+    .map(chrono::DateTime::<chrono::Utc>::from)
+}
+
+fn read_metadata(mod_dir: &Path) -> Result<Option<ModMetadata>, ModSourceGeneratorError> {
+  for file_name in METADATA_FILE_NAMES {
+    let path = mod_dir.join(file_name);
+    if path.is_file() {
+      let metadata = serde_json::from_str(&fs::read_to_string(&path)?).map_err(|err| {
+        ModSourceGeneratorError::Validation(format!("{} is invalid: {err}", path.display()))
+      })?;
+      return Ok(Some(metadata));
+    }
+  }
+  Ok(None)
+}
+
+// Newest versions first
+fn sort_versions(versions: &mut [ModVersion]) {
+  versions.sort_by(|a, b| {
+    let by_version = match (parse_mod_version(&a.version), parse_mod_version(&b.version)) {
+      (Some(a), Some(b)) => b.cmp(&a),
+      _ => std::cmp::Ordering::Equal,
+    };
+    by_version.then_with(|| b.published_date.cmp(&a.published_date))
+  });
+}
+
+fn validate_games(games: &[String], context: &str, problems: &mut Vec<String>) {
+  for game in games {
+    if SupportedGame::from_str(game).is_err() {
+      problems.push(format!("{context}: '{game}' is not a supported game"));
+    }
+  }
+}
+
+fn generate_mod(
+  mod_dir: &Path,
+  mod_id: &str,
+  base_url: &str,
+  warnings: &mut Vec<String>,
+  problems: &mut Vec<String>,
+) -> Result<Option<ModInfo>, ModSourceGeneratorError> {
+  let metadata = match read_metadata(mod_dir)? {
+    Some(metadata) => metadata,
+    None => {
+      warnings.push(format!("{mod_id}: skipped, it has no mod.json"));
+      return Ok(None);
+    }
+  };
+
+  // version -> the archives that make it up, along with their platform
+  let mut releases: BTreeMap<String, Vec<(&'static str, PathBuf)>> = BTreeMap::new();
+  let mut entries: Vec<PathBuf> = fs::read_dir(mod_dir)?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| path.is_file())
+    .collect();
+  entries.sort();
+  for path in entries {
+    let file_name = path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    if detect_archive_format(&path).is_err() {
+      continue;
+    }
+    match parse_release_name(&archive_name(&path).unwrap_or_default()) {
+      Some((platform, version)) => {
+        let platforms = releases.entry(version.clone()).or_default();
+        if platforms.iter().any(|(existing, _)| *existing == platform) {
+          problems.push(format!(
+            "{mod_id}: more than one {platform} archive for version {version}"
+          ));
+        }
+        platforms.push((platform, path));
+      }
+      None => warnings.push(format!(
+        "{mod_id}: skipped {file_name}, its name doesn't end in -<platform>-v<version>"
+      )),
+    }
+  }
+  if releases.is_empty() {
+    warnings.push(format!("{mod_id}: skipped, it has no release archives"));
+    return Ok(None);
+  }
+
+  let display_name = match &metadata.display_name {
+    Some(display_name) if !display_name.trim().is_empty() => display_name.clone(),
+    _ => {
+      warnings.push(format!("{mod_id}: no displayName, using the mod's id"));
+      mod_id.to_owned()
+    }
+  };
+  validate_games(&metadata.supported_games, mod_id, problems);
+
+  let mut versions = Vec::new();
+  for (version, archives) in releases {
+    let version_metadata = metadata
+      .versions
+      .iter()
+      .find(|(key, _)| normalize_mod_version(key) == version)
+      .map(|(_, version_metadata)| version_metadata.clone())
+      .unwrap_or_default();
+    let mut assets: HashMap<String, Option<String>> = PLATFORMS
+      .iter()
+      .map(|platform| (platform.to_string(), None))
+      .collect();
+    let mut published = None;
+    for (platform, path) in &archives {
+      let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+      assets.insert(
+        platform.to_string(),
+        Some(file_url(base_url, mod_id, &file_name)),
+      );
+      published = published.max(modified_date(path));
+    }
+    let published_date = match version_metadata.published_date {
+      Some(published_date) => {
+        if chrono::DateTime::parse_from_rfc3339(&published_date).is_err() {
+          problems.push(format!(
+            "{mod_id}: publishedDate of version {version} isn't an RFC 3339 date"
+          ));
+        }
+        published_date
+      }
+      None => published
+        .unwrap_or_else(chrono::Utc::now)
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    };
+    let supported_games = version_metadata
+      .supported_games
+      .unwrap_or_else(|| metadata.supported_games.clone());
+    if supported_games.is_empty() {
+      problems.push(format!(
+        "{mod_id}: version {version} doesn't list any supported games"
+      ));
+    }
+    validate_games(&supported_games, &format!("{mod_id} {version}"), problems);
+    versions.push(ModVersion {
+      version,
+      published_date,
+      assets,
+      supported_games: Some(supported_games),
+      dependencies: version_metadata.dependencies,
+      settings: version_metadata.settings,
+    });
+  }
+  sort_versions(&mut versions);
+
+  let mut supported_games = metadata.supported_games.clone();
+  if supported_games.is_empty() {
+    for version in &versions {
+      for game in version.supported_games.iter().flatten() {
+        if !supported_games.contains(game) {
+          supported_games.push(game.clone());
+        }
+      }
+    }
+  }
+  let art_url = |url: &Option<String>, file_name: &str| {
+    url.clone().or_else(|| {
+      mod_dir
+        .join(file_name)
+        .is_file()
+        .then(|| file_url(base_url, mod_id, file_name))
+    })
+  };
+
+  Ok(Some(ModInfo {
+    display_name,
+    description: metadata.description.clone(),
+    authors: metadata.authors.clone(),
+    tags: metadata.tags.clone(),
+    supported_games,
+    website_url: metadata.website_url.clone(),
+    versions,
+    per_game_config: metadata.per_game_config.clone(),
+    cover_art_url: art_url(&metadata.cover_art_url, "cover.png"),
+    thumbnail_art_url: art_url(&metadata.thumbnail_art_url, "thumbnail.png"),
+    external_link: metadata.external_link.clone(),
+  }))
+}
+
+// `base_url` is where the contents of `source_dir` will be served from
+pub fn generate_mod_source(
+  source_dir: &Path,
+  source_name: &str,
+  base_url: &str,
+  output_path: &Path,
+) -> Result<GeneratedModSource, ModSourceGeneratorError> {
+  let mut problems = Vec::new();
+  if source_name.trim().is_empty() {
+    problems.push("a source name is required".to_owned());
+  }
+  if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+    problems.push(format!("'{base_url}' is not an http(s) url"));
+  }
+  if !source_dir.is_dir() {
+    problems.push(format!("{} is not a folder", source_dir.display()));
+  }
+  if !problems.is_empty() {
+    return Err(ModSourceGeneratorError::Validation(problems.join(", ")));
+  }
+
+  let mut warnings = Vec::new();
+  let mut mod_dirs: Vec<PathBuf> = fs::read_dir(source_dir)?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| path.is_dir())
+    .collect();
+  mod_dirs.sort();
+  let mut mods = HashMap::new();
+  for mod_dir in mod_dirs {
+    let mod_id = match mod_dir.file_name() {
+      Some(name) => name.to_string_lossy().into_owned(),
+      None => continue,
+    };
+    if let Some(mod_info) = generate_mod(&mod_dir, &mod_id, base_url, &mut warnings, &mut problems)?
+    {
+      mods.insert(mod_id, mod_info);
+    }
+  }
+  if !problems.is_empty() {
+    return Err(ModSourceGeneratorError::Validation(format!(
+      "The mod source is invalid: {}",
+      problems.join(", ")
+    )));
+  }
+
+  let source = ModSourceData {
+    schema_version: SCHEMA_VERSION.to_owned(),
+    source_name: source_name.to_owned(),
+    last_updated: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    mods,
+    texture_packs: HashMap::new(),
+  };
+  // going through a `Value` sorts the mods by id, which keeps the file diffable between runs
+  let json = serde_json::to_string_pretty(&serde_json::to_value(&source)?)?;
+  // make sure the launcher will be able to read what was written
+  serde_json::from_str::<ModSourceData>(&json)?;
+  fs::write(output_path, json)?;
+
+  let version_count = source
+    .mods
+    .values()
+    .map(|mod_info| mod_info.versions.len())
+    .sum();
+  log::info!(
+    "Generated mod source {} with {} mods and {} versions",
+    output_path.display(),
+    source.mods.len(),
+    version_count
+  );
+  Ok(GeneratedModSource {
+    output_path: output_path.to_string_lossy().into_owned(),
+    mod_count: source.mods.len(),
+    version_count,
+    warnings,
+  })
+}
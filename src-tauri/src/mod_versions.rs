@@ -0,0 +1,187 @@
+// Keeps previously installed versions of a mod around so they can be switched back to
+//
+// The installed version lives directly in the mod's folder, as it always has, so nothing that runs
+// the mod has to know about this.  Installing over it first moves it into
+// `.previous-versions/<version>` in the same folder, if the install then fails it is moved back,
+// otherwise it is kept until there are more previous versions than the configured retention count.
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+pub const PREVIOUS_VERSIONS_DIR: &str = ".previous-versions";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ModVersionsError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error("{0}")]
+  InvalidVersion(String),
+  #[error("{0}")]
+  NotFound(String),
+}
+
+fn previous_version_dir(mod_dir: &Path, version: &str) -> Result<PathBuf, ModVersionsError> {
+  if version.is_empty() || version == "." || version == ".." || version.contains(['/', '\\']) {
+    return Err(ModVersionsError::InvalidVersion(format!(
+      "'{version}' can't be kept as a previous version"
+    )));
+  }
+  Ok(mod_dir.join(PREVIOUS_VERSIONS_DIR).join(version))
+}
+
+// Everything in the mod's folder that belongs to the installed version
+fn installed_entries(mod_dir: &Path) -> Result<Vec<PathBuf>, ModVersionsError> {
+  if !mod_dir.exists() {
+    return Ok(Vec::new());
+  }
+  Ok(
+    fs::read_dir(mod_dir)?
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| entry.file_name() != PREVIOUS_VERSIONS_DIR)
+      .map(|entry| entry.path())
+      .collect(),
+  )
+}
+
+fn remove_path(path: &Path) -> Result<(), std::io::Error> {
+  if path.is_dir() && !path.is_symlink() {
+    fs::remove_dir_all(path)
+  } else {
+    fs::remove_file(path)
+  }
+}
+
+// Renames every entry, if one fails the ones already moved are moved back by name so nothing is
+// left split between the two folders
+fn move_entries(moves: &[(PathBuf, PathBuf)]) -> Result<(), std::io::Error> {
+  for (index, (from, to)) in moves.iter().enumerate() {
+    if let Err(err) = fs::rename(from, to) {
+      for (from, to) in moves[..index].iter().rev() {
+        if let Err(undo_err) = fs::rename(to, from) {
+          log::error!(
+            "Unable to move {} back to {}: {}",
+            to.display(),
+            from.display(),
+            undo_err
+          );
+        }
+      }
+      return Err(err);
+    }
+  }
+  Ok(())
+}
+
+// The versions that can be switched back to, most recently replaced first
+pub fn previous_versions(mod_dir: &Path) -> Result<Vec<String>, ModVersionsError> {
+  let versions_dir = mod_dir.join(PREVIOUS_VERSIONS_DIR);
+  if !versions_dir.exists() {
+    return Ok(Vec::new());
+  }
+  let mut versions: Vec<(std::time::SystemTime, String)> = fs::read_dir(versions_dir)?
+    .filter_map(|entry| entry.ok())
+    .filter(|entry| entry.path().is_dir())
+    .map(|entry| {
+      let modified = entry
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(std::time::UNIX_EPOCH);
+      (modified, entry.file_name().to_string_lossy().into_owned())
+    })
+    .collect();
+  versions.sort_by(|a, b| b.cmp(a));
+  Ok(versions.into_iter().map(|(_, version)| version).collect())
+}
+
+pub fn has_previous_version(mod_dir: &Path, version: &str) -> bool {
+  previous_version_dir(mod_dir, version).is_ok_and(|dir| dir.is_dir())
+}
+
+// Moves the installed version aside, replacing anything already kept under the same version
+pub fn keep_installed_version(mod_dir: &Path, version: &str) -> Result<(), ModVersionsError> {
+  let entries = installed_entries(mod_dir)?;
+  if entries.is_empty() {
+    return Ok(());
+  }
+  let version_dir = previous_version_dir(mod_dir, version)?;
+  if version_dir.exists() {
+    fs::remove_dir_all(&version_dir)?;
+  }
+  fs::create_dir_all(&version_dir)?;
+  log::info!(
+    "Keeping version {} of {} for rollback",
+    version,
+    mod_dir.display()
+  );
+  let moves: Vec<(PathBuf, PathBuf)> = entries
+    .into_iter()
+    .filter_map(|entry| {
+      let destination = version_dir.join(entry.file_name()?);
+      Some((entry, destination))
+    })
+    .collect();
+  if let Err(err) = move_entries(&moves) {
+    // everything was moved back, so the installed version is untouched
+    let _ = fs::remove_dir(&version_dir);
+    return Err(err.into());
+  }
+  Ok(())
+}
+
+pub fn remove_installed_version(mod_dir: &Path) -> Result<(), ModVersionsError> {
+  for entry in installed_entries(mod_dir)? {
+    remove_path(&entry)?;
+  }
+  Ok(())
+}
+
+// Replaces whatever is installed with a previous version, callers that want the installed version
+// kept have to move it aside first
+pub fn restore_previous_version(mod_dir: &Path, version: &str) -> Result<(), ModVersionsError> {
+  let version_dir = previous_version_dir(mod_dir, version)?;
+  if !version_dir.is_dir() {
+    return Err(ModVersionsError::NotFound(format!(
+      "Version {version} of this mod wasn't kept"
+    )));
+  }
+  remove_installed_version(mod_dir)?;
+  log::info!("Restoring version {} of {}", version, mod_dir.display());
+  let moves = fs::read_dir(&version_dir)?
+    .map(|entry| entry.map(|entry| (entry.path(), mod_dir.join(entry.file_name()))))
+    .collect::<Result<Vec<_>, _>>()?;
+  // on failure the previous version is still kept whole and nothing is installed
+  move_entries(&moves)?;
+  fs::remove_dir_all(version_dir)?;
+  Ok(())
+}
+
+// Drops the kept copy of the installed version, it is stale once that version has been reinstalled,
+// and the oldest versions beyond `retention`
+pub fn prune_previous_versions(
+  mod_dir: &Path,
+  installed_version: &str,
+  retention: usize,
+) -> Result<(), ModVersionsError> {
+  let versions_dir = mod_dir.join(PREVIOUS_VERSIONS_DIR);
+  if !versions_dir.exists() {
+    return Ok(());
+  }
+  if has_previous_version(mod_dir, installed_version) {
+    fs::remove_dir_all(previous_version_dir(mod_dir, installed_version)?)?;
+  }
+  let versions = previous_versions(mod_dir)?;
+  for version in versions.into_iter().skip(retention) {
+    log::info!(
+      "Deleting previous version {} of {}",
+      version,
+      mod_dir.display()
+    );
+    fs::remove_dir_all(versions_dir.join(version))?;
+  }
+  if fs::read_dir(&versions_dir)?.next().is_none() {
+    fs::remove_dir(versions_dir)?;
+  }
+  Ok(())
+}
@@ -0,0 +1,327 @@
+// Keeps track of the game processes the launcher has started
+//
+// Every launch registers its `gk` process here and a background task polls it until it exits,
+// at which point it is removed again.  The frontend is kept up to date through the
+// `game_started` and `game_exited` events.
+
+use std::{
+  collections::HashMap,
+  path::Path,
+  process::{Child, ExitStatus},
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use crate::{
+  config::LauncherConfig,
+  crash_reports::capture_crash,
+  session_history::{append_session, session_log_path, PlaySession},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessRegistryError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  TauriEvent(#[from] tauri::Error),
+  #[error("{0}")]
+  NotRunning(String),
+  #[error("{0}")]
+  Signal(String),
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningGame {
+  pub pid: u32,
+  pub game_name: String,
+  pub mod_name: Option<String>,
+  pub source_name: Option<String>,
+  // tooling version for the vanilla game, the mod's version otherwise
+  pub version: Option<String>,
+  pub start_time: String,
+  pub log_file: Option<String>,
+}
+
+impl RunningGame {
+  pub fn new(
+    pid: u32,
+    game_name: &str,
+    mod_info: Option<(&str, &str)>,
+    version: Option<String>,
+    log_file: Option<String>,
+  ) -> RunningGame {
+    RunningGame {
+      pid,
+      game_name: game_name.to_owned(),
+      source_name: mod_info.map(|(source_name, _)| source_name.to_owned()),
+      mod_name: mod_info.map(|(_, mod_name)| mod_name.to_owned()),
+      version,
+      start_time: chrono::Utc::now().to_rfc3339(),
+      log_file,
+    }
+  }
+
+  // `mod_info` is the (source, mod) pair, or `None` for the vanilla game
+  pub fn is_instance_of(&self, game_name: &str, mod_info: Option<(&str, &str)>) -> bool {
+    self.game_name == game_name
+      && match mod_info {
+        Some((source_name, mod_name)) => {
+          self.source_name.as_deref() == Some(source_name)
+            && self.mod_name.as_deref() == Some(mod_name)
+        }
+        None => self.mod_name.is_none(),
+      }
+  }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct GameExitedPayload {
+  #[serde(flatten)]
+  game: RunningGame,
+  exit_code: Option<i32>,
+  stop_requested: bool,
+  crash_report_id: Option<String>,
+}
+
+pub struct GameExit {
+  pub status: ExitStatus,
+  // the user asked for the game to be stopped, so a non-zero exit is not a crash
+  pub stop_requested: bool,
+}
+
+struct TrackedProcess {
+  info: RunningGame,
+  child: Arc<Mutex<Child>>,
+  stop_requested: bool,
+}
+
+#[derive(Default)]
+pub struct ProcessRegistry {
+  processes: HashMap<u32, TrackedProcess>,
+}
+
+impl ProcessRegistry {
+  pub fn list(&self) -> Vec<RunningGame> {
+    let mut running: Vec<RunningGame> = self
+      .processes
+      .values()
+      .map(|process| process.info.clone())
+      .collect();
+    running.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+    running
+  }
+
+  pub fn find(&self, game_name: &str, mod_info: Option<(&str, &str)>) -> Option<&RunningGame> {
+    self
+      .processes
+      .values()
+      .map(|process| &process.info)
+      .find(|info| info.is_instance_of(game_name, mod_info))
+  }
+
+  // Concurrent instances each write their own log, so a log that a running game still writes to
+  // is never rotated away from under it
+  pub fn free_log_name(&self, log_name: &str) -> String {
+    let stem = log_name.strip_suffix(".log").unwrap_or(log_name);
+    (1..)
+      .map(|instance| match instance {
+        1 => log_name.to_owned(),
+        _ => format!("{stem}.instance{instance}.log"),
+      })
+      .find(|name| {
+        !self.processes.values().any(|process| {
+          process
+            .info
+            .log_file
+            .as_deref()
+            .and_then(|log_file| Path::new(log_file).file_name())
+            .is_some_and(|file_name| file_name == name.as_str())
+        })
+      })
+      .unwrap()
+  }
+
+  pub fn register(&mut self, app_handle: &tauri::AppHandle, child: Child, info: RunningGame) {
+    log::info!(
+      "Tracking {} (pid {}) started at {}",
+      info.game_name,
+      info.pid,
+      info.start_time
+    );
+    if let Err(err) = app_handle.emit("game_started", &info) {
+      log::error!("Failed to emit game_started event: {}", err);
+    }
+    self.processes.insert(
+      info.pid,
+      TrackedProcess {
+        info,
+        child: Arc::new(Mutex::new(child)),
+        stop_requested: false,
+      },
+    );
+  }
+
+  fn tracked(&self, pid: u32) -> Result<&TrackedProcess, ProcessRegistryError> {
+    self
+      .processes
+      .get(&pid)
+      .ok_or_else(|| ProcessRegistryError::NotRunning(format!("No game is running with pid {pid}")))
+  }
+
+  fn tracked_mut(&mut self, pid: u32) -> Result<&mut TrackedProcess, ProcessRegistryError> {
+    self
+      .processes
+      .get_mut(&pid)
+      .ok_or_else(|| ProcessRegistryError::NotRunning(format!("No game is running with pid {pid}")))
+  }
+
+  // Asks the game to close, giving it a chance to shut down cleanly
+  pub fn stop(&mut self, pid: u32) -> Result<(), ProcessRegistryError> {
+    self.tracked_mut(pid)?.stop_requested = true;
+    log::info!("Requesting pid {} to exit", pid);
+    #[cfg(windows)]
+    {
+      use std::os::windows::process::CommandExt;
+      let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .creation_flags(0x08000000)
+        .status()?;
+      if !status.success() {
+        return Err(ProcessRegistryError::Signal(format!(
+          "Unable to ask pid {pid} to exit"
+        )));
+      }
+    }
+    #[cfg(not(windows))]
+    {
+      let sys_pid = sysinfo::Pid::from_u32(pid);
+      let mut system = sysinfo::System::new();
+      system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sys_pid]), true);
+      let sent = system
+        .process(sys_pid)
+        .and_then(|process| process.kill_with(sysinfo::Signal::Term))
+        .unwrap_or(false);
+      if !sent {
+        return Err(ProcessRegistryError::Signal(format!(
+          "Unable to ask pid {pid} to exit"
+        )));
+      }
+    }
+    Ok(())
+  }
+
+  pub fn kill(&mut self, pid: u32) -> Result<(), ProcessRegistryError> {
+    let process = self.tracked_mut(pid)?;
+    process.stop_requested = true;
+    log::info!("Killing pid {}", pid);
+    let mut child = process
+      .child
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner());
+    child.kill()?;
+    Ok(())
+  }
+}
+
+fn record_session(
+  app_handle: &tauri::AppHandle,
+  info: &RunningGame,
+  exit_code: Option<i32>,
+  crashed: bool,
+) {
+  let end_time = chrono::Utc::now();
+  let duration_seconds = chrono::DateTime::parse_from_rfc3339(&info.start_time)
+    .map(|start| {
+      (end_time - start.with_timezone(&chrono::Utc))
+        .num_seconds()
+        .max(0) as u64
+    })
+    .unwrap_or(0);
+  let session = PlaySession {
+    game_name: info.game_name.clone(),
+    mod_name: info.mod_name.clone(),
+    source_name: info.source_name.clone(),
+    version: info.version.clone(),
+    start_time: info.start_time.clone(),
+    end_time: end_time.to_rfc3339(),
+    duration_seconds,
+    exit_code,
+    crashed,
+  };
+  match session_log_path(app_handle) {
+    Some(log_path) => {
+      if let Err(err) = append_session(&log_path, &session) {
+        log::error!("Unable to record play session: {}", err);
+      }
+    }
+    None => log::error!("Unable to determine where to record play sessions"),
+  }
+}
+
+// Polls the registered process until it exits, then removes it from the registry and lets the
+// frontend know
+pub async fn wait_for_game_exit(
+  app_handle: &tauri::AppHandle,
+  pid: u32,
+) -> Result<GameExit, ProcessRegistryError> {
+  let registry = app_handle.state::<tokio::sync::Mutex<ProcessRegistry>>();
+  let child = registry.lock().await.tracked(pid)?.child.clone();
+  let result = loop {
+    let status = child
+      .lock()
+      .unwrap_or_else(|poisoned| poisoned.into_inner())
+      .try_wait();
+    match status {
+      Ok(Some(status)) => break Ok(status),
+      Ok(None) => tokio::time::sleep(Duration::from_millis(500)).await,
+      Err(err) => break Err(err),
+    }
+  };
+
+  let mut stop_requested = false;
+  // take it out of the registry first, so the lock isn't held while the crash is captured
+  let process = registry.lock().await.processes.remove(&pid);
+  if let Some(process) = process {
+    log::info!("{} (pid {}) has exited", process.info.game_name, pid);
+    stop_requested = process.stop_requested;
+    let exit_code = result.as_ref().ok().and_then(|status| status.code());
+    let crashed = !stop_requested && exit_code != Some(0);
+    record_session(app_handle, &process.info, exit_code, crashed);
+    let mut crash_report_id = None;
+    if crashed {
+      let install_dir = app_handle
+        .state::<tokio::sync::Mutex<LauncherConfig>>()
+        .lock()
+        .await
+        .installation_dir
+        .clone();
+      match capture_crash(
+        app_handle,
+        &process.info,
+        install_dir.as_deref().map(Path::new),
+        exit_code,
+      ) {
+        Ok(report) => crash_report_id = Some(report.id),
+        Err(err) => log::error!("Unable to capture crash report: {}", err),
+      }
+    }
+    app_handle.emit(
+      "game_exited",
+      GameExitedPayload {
+        game: process.info,
+        exit_code,
+        stop_requested,
+        crash_report_id,
+      },
+    )?;
+  }
+  Ok(GameExit {
+    status: result?,
+    stop_requested,
+  })
+}
@@ -0,0 +1,425 @@
+// Client for goalc's network REPL, so the launcher can host the REPL itself instead of relying on
+// a terminal emulator being available
+//
+// goalc is started headless with its output piped back to the frontend, and forms are sent over
+// its TCP REPL.  Every message is a little-endian `u32` length and `u32` message type header
+// followed by the form's text.  Output from both goalc and the socket is streamed to the
+// frontend as `repl_output` events, and `repl_closed` is emitted once a session goes away.
+
+use std::{
+  collections::HashMap, path::Path, process::Stdio, str::FromStr, sync::Arc, time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader},
+  net::{tcp::OwnedWriteHalf, TcpStream},
+  process::{Child, ChildStdin, Command},
+};
+
+use crate::config::SupportedGame;
+
+// goalc's listener message type for REPL input
+const REPL_MESSAGE_TYPE: u32 = 10;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error("{0}")]
+  NotConnected(String),
+  #[error("{0}")]
+  AlreadyRunning(String),
+  #[error("{0}")]
+  Connection(String),
+}
+
+// Each game listens on its own port by default, so they can be used side by side
+pub fn default_repl_port(game_name: &str) -> u16 {
+  match SupportedGame::from_str(game_name) {
+    Ok(SupportedGame::Jak2) => 8182,
+    Ok(SupportedGame::Jak3) => 8183,
+    Ok(SupportedGame::JakX) => 8184,
+    _ => 8181,
+  }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplSessionInfo {
+  pub id: u32,
+  pub game_name: String,
+  pub mod_name: Option<String>,
+  pub source_name: Option<String>,
+  pub address: String,
+  // only set when the launcher started goalc itself
+  pub pid: Option<u32>,
+}
+
+impl ReplSessionInfo {
+  fn is_instance_of(&self, game_name: &str, mod_info: Option<(&str, &str)>) -> bool {
+    self.game_name == game_name
+      && self.source_name.as_deref() == mod_info.map(|(source_name, _)| source_name)
+      && self.mod_name.as_deref() == mod_info.map(|(_, mod_name)| mod_name)
+  }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ReplOutputPayload {
+  session_id: u32,
+  // "goalc" for the compiler's own output, "repl" for anything sent back over the socket
+  source: String,
+  line: String,
+}
+
+struct ReplSession {
+  info: ReplSessionInfo,
+  // locked on its own so a slow write doesn't hold up every other session
+  writer: Arc<tokio::sync::Mutex<OwnedWriteHalf>>,
+  goalc: Option<Child>,
+  // goalc exits once its stdin is closed, so it's kept open for as long as the session lives
+  _goalc_stdin: Option<ChildStdin>,
+}
+
+#[derive(Default)]
+pub struct ReplManager {
+  sessions: HashMap<u32, ReplSession>,
+  // goalc instances that were started but aren't connected to yet, so the same REPL can't be
+  // started twice while waiting for it to listen
+  starting: HashMap<u32, ReplSessionInfo>,
+  next_id: u32,
+}
+
+impl ReplManager {
+  pub fn list(&self) -> Vec<ReplSessionInfo> {
+    let mut sessions: Vec<ReplSessionInfo> = self
+      .sessions
+      .values()
+      .map(|session| session.info.clone())
+      .collect();
+    sessions.sort_by_key(|session| session.id);
+    sessions
+  }
+
+  pub fn find(&self, game_name: &str, mod_info: Option<(&str, &str)>) -> Option<&ReplSessionInfo> {
+    self
+      .sessions
+      .values()
+      .map(|session| &session.info)
+      .chain(self.starting.values())
+      .find(|info| info.is_instance_of(game_name, mod_info))
+  }
+
+  fn next_id(&mut self) -> u32 {
+    self.next_id += 1;
+    self.next_id
+  }
+
+  fn writer(&self, id: u32) -> Result<Arc<tokio::sync::Mutex<OwnedWriteHalf>>, ReplError> {
+    self
+      .sessions
+      .get(&id)
+      .map(|session| session.writer.clone())
+      .ok_or_else(|| ReplError::NotConnected(format!("No REPL session with id {id}")))
+  }
+
+  fn remove(&mut self, id: u32) -> Result<ReplSession, ReplError> {
+    self
+      .sessions
+      .remove(&id)
+      .ok_or_else(|| ReplError::NotConnected(format!("No REPL session with id {id}")))
+  }
+}
+
+// The manager is only locked to look the session up, the write itself can take as long as it needs
+pub async fn send(
+  manager: &tokio::sync::Mutex<ReplManager>,
+  id: u32,
+  form: &str,
+) -> Result<(), ReplError> {
+  let writer = manager.lock().await.writer(id)?;
+  let mut writer = writer.lock().await;
+  writer.write_all(&encode_form(form)).await?;
+  writer.flush().await?;
+  Ok(())
+}
+
+// Disconnects from the REPL, and shuts down goalc if the launcher started it
+pub async fn close(manager: &tokio::sync::Mutex<ReplManager>, id: u32) -> Result<(), ReplError> {
+  let mut session = manager.lock().await.remove(id)?;
+  log::info!("Closing REPL session {}", id);
+  let _ = session.writer.lock().await.shutdown().await;
+  if let Some(goalc) = session.goalc.as_mut() {
+    goalc.kill().await?;
+  }
+  Ok(())
+}
+
+pub fn encode_form(form: &str) -> Vec<u8> {
+  let mut message = Vec::with_capacity(form.len() + 8);
+  message.extend_from_slice(&(form.len() as u32).to_le_bytes());
+  message.extend_from_slice(&REPL_MESSAGE_TYPE.to_le_bytes());
+  message.extend_from_slice(form.as_bytes());
+  message
+}
+
+fn emit_output(app_handle: &tauri::AppHandle, session_id: u32, source: &str, line: String) {
+  if let Err(err) = app_handle.emit(
+    "repl_output",
+    ReplOutputPayload {
+      session_id,
+      source: source.to_owned(),
+      line,
+    },
+  ) {
+    log::error!("Failed to emit repl_output event: {}", err);
+  }
+}
+
+// Forwards every line read to the frontend
+fn forward_output<R: AsyncRead + Unpin + Send + 'static>(
+  app_handle: &tauri::AppHandle,
+  session_id: u32,
+  source: &'static str,
+  reader: R,
+) -> tokio::task::JoinHandle<()> {
+  let app_handle = app_handle.clone();
+  tokio::spawn(async move {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+      match lines.next_line().await {
+        Ok(Some(line)) => emit_output(&app_handle, session_id, source, line),
+        Ok(None) => break,
+        Err(err) => {
+          log::warn!(
+            "Stopped reading {} output for REPL {}: {}",
+            source,
+            session_id,
+            err
+          );
+          break;
+        }
+      }
+    }
+  })
+}
+
+async fn connect_with_retry(
+  address: &str,
+  goalc: &mut Option<Child>,
+) -> Result<TcpStream, ReplError> {
+  let started = tokio::time::Instant::now();
+  loop {
+    match TcpStream::connect(address).await {
+      Ok(stream) => return Ok(stream),
+      Err(err) => {
+        if let Some(goalc) = goalc.as_mut() {
+          if let Some(status) = goalc.try_wait()? {
+            return Err(ReplError::Connection(format!(
+              "goalc exited before its REPL was available ({status})"
+            )));
+          }
+        }
+        if started.elapsed() > CONNECT_TIMEOUT {
+          return Err(ReplError::Connection(format!(
+            "Unable to connect to the REPL at {address}: {err}"
+          )));
+        }
+        tokio::time::sleep(CONNECT_RETRY_INTERVAL).await;
+      }
+    }
+  }
+}
+
+async fn open_session(
+  app_handle: &tauri::AppHandle,
+  mut info: ReplSessionInfo,
+  mut goalc: Option<Child>,
+) -> Result<ReplSessionInfo, ReplError> {
+  let id = info.id;
+  let manager = app_handle.state::<tokio::sync::Mutex<ReplManager>>();
+  let stream = match connect_with_retry(&info.address, &mut goalc).await {
+    Ok(stream) => stream,
+    Err(err) => {
+      manager.lock().await.starting.remove(&id);
+      if let Some(goalc) = goalc.as_mut() {
+        let _ = goalc.kill().await;
+      }
+      return Err(err);
+    }
+  };
+  log::info!("Connected to the REPL at {}", info.address);
+  let (reader, writer) = stream.into_split();
+  info.pid = goalc.as_ref().and_then(|goalc| goalc.id());
+  let goalc_stdin = goalc.as_mut().and_then(|goalc| goalc.stdin.take());
+  {
+    let mut manager = manager.lock().await;
+    manager.starting.remove(&id);
+    manager.sessions.insert(
+      id,
+      ReplSession {
+        info: info.clone(),
+        writer: Arc::new(tokio::sync::Mutex::new(writer)),
+        goalc,
+        _goalc_stdin: goalc_stdin,
+      },
+    );
+  }
+
+  // once the REPL hangs up the session is over
+  let socket_output = forward_output(app_handle, id, "repl", reader);
+  let app_handle = app_handle.clone();
+  tokio::spawn(async move {
+    let _ = socket_output.await;
+    let manager = app_handle.state::<tokio::sync::Mutex<ReplManager>>();
+    match close(&manager, id).await {
+      // already closed from the launcher's side
+      Ok(_) | Err(ReplError::NotConnected(_)) => {}
+      Err(err) => log::warn!("Unable to clean up REPL session {}: {}", id, err),
+    }
+    if let Err(err) = app_handle.emit("repl_closed", id) {
+      log::error!("Failed to emit repl_closed event: {}", err);
+    }
+  });
+  Ok(info)
+}
+
+fn session_info(
+  id: u32,
+  game_name: &str,
+  mod_info: Option<(&str, &str)>,
+  address: String,
+) -> ReplSessionInfo {
+  ReplSessionInfo {
+    id,
+    game_name: game_name.to_owned(),
+    source_name: mod_info.map(|(source_name, _)| source_name.to_owned()),
+    mod_name: mod_info.map(|(_, mod_name)| mod_name.to_owned()),
+    address,
+    pid: None,
+  }
+}
+
+fn ensure_not_running(
+  manager: &ReplManager,
+  game_name: &str,
+  mod_info: Option<(&str, &str)>,
+) -> Result<(), ReplError> {
+  match manager.find(game_name, mod_info) {
+    Some(existing) => Err(ReplError::AlreadyRunning(format!(
+      "A REPL is already open for {game_name} (session {})",
+      existing.id
+    ))),
+    None => Ok(()),
+  }
+}
+
+// Starts goalc without a terminal and connects to its REPL, `args` are the project or iso path
+// arguments for the game being worked on
+pub async fn start_goalc(
+  app_handle: &tauri::AppHandle,
+  executable_dir: &Path,
+  args: Vec<String>,
+  game_name: &str,
+  mod_info: Option<(&str, &str)>,
+  port: Option<u16>,
+) -> Result<ReplSessionInfo, ReplError> {
+  let manager = app_handle.state::<tokio::sync::Mutex<ReplManager>>();
+  // held until goalc is registered as starting, so two requests can't both start it
+  let mut manager = manager.lock().await;
+  ensure_not_running(&manager, game_name, mod_info)?;
+  let id = manager.next_id();
+  let port = port.unwrap_or_else(|| default_repl_port(game_name));
+  let executable_name = if cfg!(windows) { "goalc.exe" } else { "goalc" };
+  let mut command = Command::new(executable_dir.join(executable_name));
+  command
+    .args(args)
+    .args(["--game", game_name, "--port", &port.to_string()])
+    .current_dir(executable_dir)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true);
+  #[cfg(windows)]
+  {
+    command.creation_flags(0x08000000);
+  }
+  log::info!(
+    "Starting goalc for {} with its REPL on port {}",
+    game_name,
+    port
+  );
+  let mut goalc = command.spawn()?;
+  let info = session_info(id, game_name, mod_info, format!("127.0.0.1:{port}"));
+  manager.starting.insert(id, info.clone());
+  drop(manager);
+  if let Some(stdout) = goalc.stdout.take() {
+    forward_output(app_handle, id, "goalc", stdout);
+  }
+  if let Some(stderr) = goalc.stderr.take() {
+    forward_output(app_handle, id, "goalc", stderr);
+  }
+  open_session(app_handle, info, Some(goalc)).await
+}
+
+// Attaches to a REPL that is already listening, such as a goalc the user started themselves
+pub async fn connect(
+  app_handle: &tauri::AppHandle,
+  address: String,
+  game_name: &str,
+) -> Result<ReplSessionInfo, ReplError> {
+  let id = app_handle
+    .state::<tokio::sync::Mutex<ReplManager>>()
+    .lock()
+    .await
+    .next_id();
+  open_session(app_handle, session_info(id, game_name, None, address), None).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use tokio::{io::AsyncReadExt, net::TcpListener};
+
+  #[tokio::test]
+  async fn sends_framed_forms() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let client = TcpStream::connect(&address).await.unwrap();
+    let (mut server, _) = listener.accept().await.unwrap();
+
+    let (_reader, writer) = client.into_split();
+    let mut manager = ReplManager::default();
+    let id = manager.next_id();
+    manager.sessions.insert(
+      id,
+      ReplSession {
+        info: session_info(id, "jak1", None, address),
+        writer: Arc::new(tokio::sync::Mutex::new(writer)),
+        goalc: None,
+        _goalc_stdin: None,
+      },
+    );
+    let manager = tokio::sync::Mutex::new(manager);
+
+    let form = "(lt)";
+    send(&manager, id, form).await.unwrap();
+    let expected = encode_form(form);
+    let mut received = vec![0; expected.len()];
+    server.read_exact(&mut received).await.unwrap();
+    assert_eq!(received, expected);
+    assert_eq!(&received[..4], &(form.len() as u32).to_le_bytes());
+    assert_eq!(&received[4..8], &REPL_MESSAGE_TYPE.to_le_bytes());
+
+    close(&manager, id).await.unwrap();
+    assert!(matches!(
+      send(&manager, id, form).await,
+      Err(ReplError::NotConnected(_))
+    ));
+  }
+}
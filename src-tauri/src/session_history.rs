@@ -0,0 +1,190 @@
+// History of every play session, appended to a JSON lines file in the app's data directory
+//
+// Each line is a single session, so a partially written or corrupted line only loses that one
+// session rather than the whole history.
+
+use std::{
+  collections::BTreeMap,
+  fs::OpenOptions,
+  io::Write,
+  path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::util::file::create_dir;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionHistoryError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  JSONError(#[from] serde_json::Error),
+  #[error("{0}")]
+  InvalidQuery(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySession {
+  pub game_name: String,
+  pub mod_name: Option<String>,
+  pub source_name: Option<String>,
+  pub version: Option<String>,
+  pub start_time: String,
+  pub end_time: String,
+  pub duration_seconds: u64,
+  pub exit_code: Option<i32>,
+  pub crashed: bool,
+}
+
+impl PlaySession {
+  fn start(&self) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&self.start_time)
+      .ok()
+      .map(|time| time.with_timezone(&Utc))
+  }
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySessionQuery {
+  pub game_name: Option<String>,
+  pub mod_name: Option<String>,
+  pub source_name: Option<String>,
+  // rfc3339 timestamps, sessions that started within the range are included
+  pub from: Option<String>,
+  pub to: Option<String>,
+}
+
+impl PlaySessionQuery {
+  fn parse_bound(bound: &Option<String>) -> Result<Option<DateTime<Utc>>, SessionHistoryError> {
+    match bound {
+      Some(time) => DateTime::parse_from_rfc3339(time)
+        .map(|time| Some(time.with_timezone(&Utc)))
+        .map_err(|err| SessionHistoryError::InvalidQuery(format!("Invalid date '{time}': {err}"))),
+      None => Ok(None),
+    }
+  }
+
+  pub fn filter(
+    &self,
+    sessions: Vec<PlaySession>,
+  ) -> Result<Vec<PlaySession>, SessionHistoryError> {
+    let from = Self::parse_bound(&self.from)?;
+    let to = Self::parse_bound(&self.to)?;
+    Ok(
+      sessions
+        .into_iter()
+        .filter(|session| {
+          self
+            .game_name
+            .as_ref()
+            .is_none_or(|game| &session.game_name == game)
+            && self
+              .mod_name
+              .as_ref()
+              .is_none_or(|mod_name| session.mod_name.as_ref() == Some(mod_name))
+            && self
+              .source_name
+              .as_ref()
+              .is_none_or(|source| session.source_name.as_ref() == Some(source))
+        })
+        .filter(|session| match session.start() {
+          Some(start) => from.is_none_or(|from| start >= from) && to.is_none_or(|to| start <= to),
+          None => from.is_none() && to.is_none(),
+        })
+        .collect(),
+    )
+  }
+}
+
+#[derive(Debug, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaySessionSummary {
+  pub session_count: usize,
+  pub crash_count: usize,
+  pub total_seconds: u64,
+  pub longest_session: Option<PlaySession>,
+  // keyed by local date (YYYY-MM-DD) and ISO week (YYYY-Www)
+  pub daily_seconds: BTreeMap<String, u64>,
+  pub weekly_seconds: BTreeMap<String, u64>,
+}
+
+impl PlaySessionSummary {
+  pub fn from_sessions(sessions: &[PlaySession]) -> PlaySessionSummary {
+    let mut summary = PlaySessionSummary {
+      session_count: sessions.len(),
+      ..Default::default()
+    };
+    for session in sessions {
+      summary.total_seconds += session.duration_seconds;
+      if session.crashed {
+        summary.crash_count += 1;
+      }
+      if summary
+        .longest_session
+        .as_ref()
+        .is_none_or(|longest| session.duration_seconds > longest.duration_seconds)
+      {
+        summary.longest_session = Some(session.clone());
+      }
+      if let Some(start) = session.start() {
+        let local_start = start.with_timezone(&Local);
+        *summary
+          .daily_seconds
+          .entry(local_start.format("%Y-%m-%d").to_string())
+          .or_insert(0) += session.duration_seconds;
+        *summary
+          .weekly_seconds
+          .entry(local_start.format("%G-W%V").to_string())
+          .or_insert(0) += session.duration_seconds;
+      }
+    }
+    summary
+  }
+}
+
+pub fn session_log_path(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+  app_handle
+    .path()
+    .app_data_dir()
+    .ok()
+    .map(|data_dir| data_dir.join("play-sessions.jsonl"))
+}
+
+pub fn append_session(log_path: &Path, session: &PlaySession) -> Result<(), SessionHistoryError> {
+  if let Some(parent) = log_path.parent() {
+    create_dir(&parent.to_path_buf())?;
+  }
+  let mut file = OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(log_path)?;
+  let mut line = serde_json::to_string(session)?;
+  line.push('\n');
+  file.write_all(line.as_bytes())?;
+  Ok(())
+}
+
+pub fn read_sessions(log_path: &Path) -> Result<Vec<PlaySession>, SessionHistoryError> {
+  if !log_path.exists() {
+    return Ok(vec![]);
+  }
+  let contents = std::fs::read_to_string(log_path)?;
+  Ok(
+    contents
+      .lines()
+      .filter(|line| !line.trim().is_empty())
+      .filter_map(|line| match serde_json::from_str::<PlaySession>(line) {
+        Ok(session) => Some(session),
+        Err(err) => {
+          log::warn!("Skipping unreadable play session entry: {}", err);
+          None
+        }
+      })
+      .collect(),
+  )
+}
@@ -1,8 +1,10 @@
+pub mod archive;
+pub mod disc_image;
 pub mod file;
 pub mod game_milestones;
 pub mod game_tests;
 pub mod network;
 pub mod os;
 pub mod process;
-pub mod tar;
+pub mod terminal;
 pub mod zip;
@@ -0,0 +1,901 @@
+// Extracts the archives we download or are handed by the user
+//
+// Releases, mods and texture packs aren't guaranteed to be packaged the same way on every
+// platform, so instead of assuming a format from the OS (or the file's extension) the format is
+// detected from the first few bytes of the file.
+//
+// Mods and texture packs come from third parties, so every entry is checked before it's written:
+// nothing may land outside of the destination folder (absolute paths, `..` components, or links
+// pointing outside of it), and archives that would extract to an absurd size are rejected.
+//
+// Entries are streamed from the archive to disk through a small buffer, with progress reported
+// after every entry so large texture packs don't appear to hang.
+
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter, ErrorKind, Read, Write},
+  path::{Component, Path, PathBuf},
+  time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use tauri::Emitter;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error(transparent)]
+  Zip(#[from] zip::result::ZipError),
+  #[error("{0}")]
+  UnsupportedFormat(String),
+  #[error("Refusing to extract '{entry}': {reason}")]
+  UnsafeEntry { entry: String, reason: String },
+}
+
+fn unsafe_entry(entry: &Path, reason: &str) -> ArchiveError {
+  ArchiveError::UnsafeEntry {
+    entry: entry.to_string_lossy().into_owned(),
+    reason: reason.to_owned(),
+  }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveFormat {
+  Zip,
+  TarGz,
+  TarXz,
+  TarZst,
+}
+
+// Nothing we install comes close to this once extracted, anything bigger is assumed to be a zip
+// bomb
+const MAX_EXTRACTED_SIZE: u64 = 32 * 1024 * 1024 * 1024;
+
+const EXTRACT_BUFFER_SIZE: usize = 256 * 1024;
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+// Extensions we strip to get an archive's name, longest first so `.tar.gz` wins over `.gz`
+const ARCHIVE_EXTENSIONS: [&str; 7] = [
+  ".tar.gz", ".tar.xz", ".tar.zst", ".tgz", ".txz", ".tzst", ".zip",
+];
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionProgress {
+  pub archive_name: String,
+  pub entry: String,
+  pub entries_extracted: usize,
+  // tarballs have to be read in full to know how many entries they hold
+  pub total_entries: Option<usize>,
+  pub bytes_extracted: u64,
+}
+
+impl ExtractionProgress {
+  fn new(archive_path: &Path, total_entries: Option<usize>) -> ExtractionProgress {
+    ExtractionProgress {
+      archive_name: archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+      entry: String::new(),
+      entries_extracted: 0,
+      total_entries,
+      bytes_extracted: 0,
+    }
+  }
+
+  fn finish_entry(&mut self, entry: &str, bytes: u64) {
+    self.entry = entry.to_owned();
+    self.entries_extracted += 1;
+    self.bytes_extracted += bytes;
+  }
+}
+
+// Reports extraction progress to the frontend as `extraction_progress` events, at most every
+// `PROGRESS_EVENT_INTERVAL` apart other than the final entry
+pub fn emit_extraction_progress(
+  app_handle: &tauri::AppHandle,
+) -> impl FnMut(&ExtractionProgress) + '_ {
+  let mut last_emitted: Option<Instant> = None;
+  move |progress| {
+    let is_last = progress.total_entries == Some(progress.entries_extracted);
+    if !is_last && last_emitted.is_some_and(|last| last.elapsed() < PROGRESS_EVENT_INTERVAL) {
+      return;
+    }
+    last_emitted = Some(Instant::now());
+    if let Err(err) = app_handle.emit("extraction_progress", progress) {
+      log::error!("Failed to emit extraction_progress event: {}", err);
+    }
+  }
+}
+
+impl ArchiveFormat {
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ArchiveFormat::Zip => "zip",
+      ArchiveFormat::TarGz => "tar.gz",
+      ArchiveFormat::TarXz => "tar.xz",
+      ArchiveFormat::TarZst => "tar.zst",
+    }
+  }
+
+  fn from_magic_bytes(header: &[u8]) -> Option<ArchiveFormat> {
+    if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+      Some(ArchiveFormat::Zip)
+    } else if header.starts_with(&[0x1f, 0x8b]) {
+      Some(ArchiveFormat::TarGz)
+    } else if header.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]) {
+      Some(ArchiveFormat::TarXz)
+    } else if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+      Some(ArchiveFormat::TarZst)
+    } else {
+      None
+    }
+  }
+}
+
+pub fn detect_archive_format(archive_path: &Path) -> Result<ArchiveFormat, ArchiveError> {
+  let mut header = Vec::with_capacity(6);
+  File::open(archive_path)?.take(6).read_to_end(&mut header)?;
+  ArchiveFormat::from_magic_bytes(&header).ok_or_else(|| {
+    ArchiveError::UnsupportedFormat(format!(
+      "{} is not a zip, tar.gz, tar.xz or tar.zst archive",
+      archive_path.display()
+    ))
+  })
+}
+
+// The file name without its archive extension(s), ie. `my-mod.tar.gz` becomes `my-mod`
+pub fn archive_name(archive_path: &Path) -> Option<String> {
+  let file_name = archive_path.file_name()?.to_string_lossy();
+  let lowercase_name = file_name.to_lowercase();
+  let name = ARCHIVE_EXTENSIONS
+    .iter()
+    .find(|extension| lowercase_name.ends_with(*extension))
+    .map(|extension| &file_name[..file_name.len() - extension.len()])
+    .unwrap_or(&file_name);
+  if name.is_empty() {
+    return None;
+  }
+  Some(name.to_owned())
+}
+
+// The entry's path relative to the destination, as long as it can't escape it
+fn sanitize_entry_path(entry_path: &Path) -> Result<PathBuf, ArchiveError> {
+  let mut relative_path = PathBuf::new();
+  for component in entry_path.components() {
+    match component {
+      Component::Normal(part) => relative_path.push(part),
+      Component::CurDir => {}
+      Component::ParentDir => return Err(unsafe_entry(entry_path, "it contains a `..` component")),
+      Component::RootDir | Component::Prefix(_) => {
+        return Err(unsafe_entry(entry_path, "it is an absolute path"))
+      }
+    }
+  }
+  Ok(relative_path)
+}
+
+// Links may only point at something else that was extracted.  `link_dir` is the folder (relative
+// to the destination) that the link's target is relative to.
+fn check_link_target(
+  entry_path: &Path,
+  link_dir: &Path,
+  target: &Path,
+) -> Result<(), ArchiveError> {
+  let mut depth = link_dir.components().count();
+  for component in target.components() {
+    match component {
+      Component::Normal(_) => depth += 1,
+      Component::CurDir => {}
+      Component::ParentDir => {
+        depth = depth
+          .checked_sub(1)
+          .ok_or_else(|| unsafe_entry(entry_path, "it links to something outside of the archive"))?
+      }
+      Component::RootDir | Component::Prefix(_) => {
+        return Err(unsafe_entry(entry_path, "it links to an absolute path"))
+      }
+    }
+  }
+  Ok(())
+}
+
+// Where an entry gets written, creating the folders leading up to it.  Link targets are only
+// checked on their own, a chain of links can still lead outside of the destination, so nothing is
+// ever written through a link that's already on disk.  `root` has to be canonical.
+fn prepare_destination(
+  root: &Path,
+  entry_path: &Path,
+  relative_path: &Path,
+) -> Result<PathBuf, ArchiveError> {
+  let components: Vec<Component> = relative_path.components().collect();
+  let mut destination = root.to_path_buf();
+  for (i, component) in components.iter().enumerate() {
+    destination.push(component);
+    let is_last = i + 1 == components.len();
+    match std::fs::symlink_metadata(&destination) {
+      Ok(metadata) if metadata.file_type().is_symlink() => {
+        if !is_last {
+          return Err(unsafe_entry(
+            entry_path,
+            "it would be written through a link",
+          ));
+        }
+        // replaced rather than followed
+        std::fs::remove_file(&destination)?;
+      }
+      Ok(_) => {}
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        if !is_last {
+          std::fs::create_dir(&destination)?;
+        }
+      }
+      Err(err) => return Err(err.into()),
+    }
+  }
+  let parent = destination.parent().unwrap_or(root).canonicalize()?;
+  if !parent.starts_with(root) {
+    return Err(unsafe_entry(
+      entry_path,
+      "it would be written outside of the destination",
+    ));
+  }
+  Ok(destination)
+}
+
+// Something that was already extracted, without following any links on the way to it
+fn existing_entry(
+  root: &Path,
+  entry_path: &Path,
+  relative_path: &Path,
+) -> Result<PathBuf, ArchiveError> {
+  let mut path = root.to_path_buf();
+  for component in relative_path.components() {
+    path.push(component);
+    match std::fs::symlink_metadata(&path) {
+      Ok(metadata) if metadata.file_type().is_symlink() => {
+        return Err(unsafe_entry(
+          entry_path,
+          "it links to something through a link",
+        ))
+      }
+      Ok(_) => {}
+      Err(_) => {
+        return Err(unsafe_entry(
+          entry_path,
+          "it links to something that wasn't extracted",
+        ))
+      }
+    }
+  }
+  Ok(path)
+}
+
+fn check_extracted_size(entry_path: &Path, extracted_size: u64) -> Result<(), ArchiveError> {
+  if extracted_size > MAX_EXTRACTED_SIZE {
+    return Err(unsafe_entry(
+      entry_path,
+      &format!(
+        "the archive extracts to more than {} GiB",
+        MAX_EXTRACTED_SIZE / 1024 / 1024 / 1024
+      ),
+    ));
+  }
+  Ok(())
+}
+
+// Checks every entry up front, the central directory makes this cheap.  The declared sizes are
+// enforced again while extracting.
+fn check_zip_entries(archive_path: &Path) -> Result<(), ArchiveError> {
+  let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+  let mut extracted_size: u64 = 0;
+  for i in 0..zip.len() {
+    let mut file = zip.by_index(i)?;
+    let entry_path = PathBuf::from(file.name());
+    let relative_path = sanitize_entry_path(&entry_path)?;
+    if file.is_symlink() {
+      let mut target = String::new();
+      let declared_size = file.size();
+      (&mut file)
+        .take(declared_size.saturating_add(1))
+        .read_to_string(&mut target)?;
+      check_link_target(
+        &entry_path,
+        relative_path.parent().unwrap_or(Path::new("")),
+        Path::new(&target),
+      )?;
+    }
+    extracted_size = extracted_size.saturating_add(file.size());
+    check_extracted_size(&entry_path, extracted_size)?;
+  }
+  Ok(())
+}
+
+// tarballs can only be read front to back, so entries are checked as they are unpacked
+fn extract_tar(
+  archive_path: &Path,
+  mut archive: tar::Archive<Box<dyn Read>>,
+  extract_dir: &Path,
+  on_progress: &mut dyn FnMut(&ExtractionProgress),
+) -> Result<(), ArchiveError> {
+  let mut progress = ExtractionProgress::new(archive_path, None);
+  let mut extracted_size: u64 = 0;
+  let root = extract_dir.canonicalize()?;
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    let entry_path = entry.path()?.into_owned();
+    let relative_path = sanitize_entry_path(&entry_path)?;
+    if relative_path.as_os_str().is_empty() {
+      continue;
+    }
+    extracted_size = extracted_size.saturating_add(entry.size());
+    check_extracted_size(&entry_path, extracted_size)?;
+
+    let destination = prepare_destination(&root, &entry_path, &relative_path)?;
+    let entry_type = entry.header().entry_type();
+    if entry_type.is_symlink() || entry_type.is_hard_link() {
+      let target = entry
+        .link_name()?
+        .ok_or_else(|| unsafe_entry(&entry_path, "it is a link without a target"))?
+        .into_owned();
+      if entry_type.is_symlink() {
+        check_link_target(
+          &entry_path,
+          relative_path.parent().unwrap_or(Path::new("")),
+          &target,
+        )?;
+      } else {
+        // hard links are relative to the root of the archive, and `tar` would resolve them
+        // against the working directory, so they're created here instead
+        let target = sanitize_entry_path(&target)
+          .map_err(|_| unsafe_entry(&entry_path, "it links to something outside of the archive"))?;
+        let target = existing_entry(&root, &entry_path, &target)?;
+        std::fs::hard_link(target, &destination)?;
+        progress.finish_entry(&entry_path.to_string_lossy(), 0);
+        on_progress(&progress);
+        continue;
+      }
+    }
+    entry.unpack(&destination)?;
+    progress.finish_entry(&entry_path.to_string_lossy(), entry.size());
+    on_progress(&progress);
+  }
+  Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, destination: &Path) -> Result<(), ArchiveError> {
+  std::os::unix::fs::symlink(target, destination)?;
+  Ok(())
+}
+
+// Symlinks need special privileges on Windows, so they're written out the same way `git` does
+#[cfg(not(unix))]
+fn create_symlink(target: &Path, destination: &Path) -> Result<(), ArchiveError> {
+  std::fs::write(destination, target.to_string_lossy().as_bytes())?;
+  Ok(())
+}
+
+// Keeps the executable bits on `gk`, `extractor` and friends
+#[cfg(unix)]
+fn set_unix_mode(destination: &Path, mode: u32, is_dir: bool) -> Result<(), ArchiveError> {
+  use std::os::unix::fs::PermissionsExt;
+
+  let mut mode = mode & 0o777;
+  if is_dir {
+    // we still need to be able to extract into it
+    mode |= 0o700;
+  }
+  std::fs::set_permissions(destination, std::fs::Permissions::from_mode(mode))?;
+  Ok(())
+}
+
+// Entries are streamed from the file into their destination, so only the central directory and a
+// fixed size buffer are ever held in memory
+fn extract_zip(
+  archive_path: &Path,
+  extract_dir: &Path,
+  on_progress: &mut dyn FnMut(&ExtractionProgress),
+) -> Result<(), ArchiveError> {
+  let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+  let mut progress = ExtractionProgress::new(archive_path, Some(zip.len()));
+  let mut extracted_size: u64 = 0;
+  let root = extract_dir.canonicalize()?;
+  for i in 0..zip.len() {
+    let mut file = zip.by_index(i)?;
+    let entry_path = PathBuf::from(file.name());
+    let relative_path = sanitize_entry_path(&entry_path)?;
+    if relative_path.as_os_str().is_empty() {
+      progress.finish_entry(file.name(), 0);
+      on_progress(&progress);
+      continue;
+    }
+    let destination = prepare_destination(&root, &entry_path, &relative_path)?;
+    let mut bytes_written = 0;
+    if file.is_dir() {
+      std::fs::create_dir_all(&destination)?;
+    } else {
+      // the declared size was checked up front, but it's the actual data that ends up on disk
+      let declared_size = file.size();
+      let is_symlink = file.is_symlink();
+      let mut reader = (&mut file).take(declared_size.saturating_add(1));
+      if is_symlink {
+        let mut target = String::new();
+        reader.read_to_string(&mut target)?;
+        bytes_written = target.len() as u64;
+        if bytes_written <= declared_size {
+          create_symlink(Path::new(&target), &destination)?;
+        }
+      } else {
+        let mut output = BufWriter::with_capacity(EXTRACT_BUFFER_SIZE, File::create(&destination)?);
+        bytes_written = std::io::copy(&mut reader, &mut output)?;
+        output.flush()?;
+      }
+      if bytes_written > declared_size {
+        return Err(unsafe_entry(
+          &entry_path,
+          "it holds more data than the archive says it does",
+        ));
+      }
+      extracted_size = extracted_size.saturating_add(bytes_written);
+      check_extracted_size(&entry_path, extracted_size)?;
+    }
+    #[cfg(unix)]
+    if let Some(mode) = file.unix_mode() {
+      if !file.is_symlink() && destination.exists() {
+        set_unix_mode(&destination, mode, file.is_dir())?;
+      }
+    }
+    progress.finish_entry(file.name(), bytes_written);
+    on_progress(&progress);
+  }
+  Ok(())
+}
+
+fn open_tar(
+  archive_path: &Path,
+  format: ArchiveFormat,
+) -> Result<tar::Archive<Box<dyn Read>>, ArchiveError> {
+  let file = BufReader::new(File::open(archive_path)?);
+  let reader: Box<dyn Read> = match format {
+    ArchiveFormat::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+    ArchiveFormat::TarXz => Box::new(xz2::read::XzDecoder::new(file)),
+    ArchiveFormat::TarZst => Box::new(zstd::stream::read::Decoder::new(file)?),
+    ArchiveFormat::Zip => {
+      return Err(ArchiveError::UnsupportedFormat(format!(
+        "{} is not a tar archive",
+        archive_path.display()
+      )))
+    }
+  };
+  Ok(tar::Archive::new(reader))
+}
+
+// If everything was extracted into a single folder, moves its contents up into `extract_dir`
+fn strip_top_level_dir(extract_dir: &Path) -> Result<(), ArchiveError> {
+  let entries = std::fs::read_dir(extract_dir)?.collect::<Result<Vec<_>, _>>()?;
+  let top_dir = match entries.as_slice() {
+    [entry] if entry.file_type()?.is_dir() => entry.path(),
+    _ => return Ok(()),
+  };
+  // moved aside first, in case it holds something with the same name as itself
+  let staging_dir = extract_dir.join(".extracting");
+  std::fs::rename(&top_dir, &staging_dir)?;
+  for entry in std::fs::read_dir(&staging_dir)? {
+    let entry = entry?;
+    std::fs::rename(entry.path(), extract_dir.join(entry.file_name()))?;
+  }
+  std::fs::remove_dir(&staging_dir)?;
+  Ok(())
+}
+
+pub fn extract_archive(
+  archive_path: &Path,
+  extract_dir: &Path,
+  strip_top_dir: bool,
+  on_progress: &mut dyn FnMut(&ExtractionProgress),
+) -> Result<ArchiveFormat, ArchiveError> {
+  let format = detect_archive_format(archive_path)?;
+  log::info!(
+    "extracting {} ({:?}) to {}",
+    archive_path.display(),
+    format,
+    extract_dir.display()
+  );
+  match format {
+    ArchiveFormat::Zip => {
+      check_zip_entries(archive_path)?;
+      extract_zip(archive_path, extract_dir, on_progress)?;
+    }
+    _ => extract_tar(
+      archive_path,
+      open_tar(archive_path, format)?,
+      extract_dir,
+      on_progress,
+    )?,
+  }
+  if strip_top_dir {
+    strip_top_level_dir(extract_dir)?;
+  }
+  Ok(format)
+}
+
+pub fn extract_and_delete_archive(
+  archive_path: &Path,
+  extract_dir: &Path,
+  strip_top_dir: bool,
+  on_progress: &mut dyn FnMut(&ExtractionProgress),
+) -> Result<ArchiveFormat, ArchiveError> {
+  let format = extract_archive(archive_path, extract_dir, strip_top_dir, on_progress)?;
+  log::info!("deleting {}", archive_path.display());
+  std::fs::remove_file(archive_path)?;
+  Ok(format)
+}
+
+// Whether any entry in the archive lives under `prefix`, without extracting anything
+pub fn archive_contains_path(archive_path: &Path, prefix: &str) -> Result<bool, ArchiveError> {
+  let format = detect_archive_format(archive_path)?;
+  if format == ArchiveFormat::Zip {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+    for i in 0..zip.len() {
+      if zip.by_index(i)?.name().starts_with(prefix) {
+        return Ok(true);
+      }
+    }
+    return Ok(false);
+  }
+  let mut tar = open_tar(archive_path, format)?;
+  for entry in tar.entries()? {
+    let entry = entry?;
+    let path = entry.path()?;
+    let path = path.to_string_lossy();
+    if path.trim_start_matches("./").starts_with(prefix) {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
+
+// Reads a single file from the root of an archive without extracting it, files bigger than
+// `max_size` are rejected
+pub fn read_archive_file(
+  archive_path: &Path,
+  file_name: &str,
+  max_size: u64,
+) -> Result<Option<Vec<u8>>, ArchiveError> {
+  let read_limited = |entry: &mut dyn Read| -> Result<Vec<u8>, ArchiveError> {
+    let mut contents = Vec::new();
+    entry.take(max_size + 1).read_to_end(&mut contents)?;
+    if contents.len() as u64 > max_size {
+      return Err(unsafe_entry(
+        Path::new(file_name),
+        &format!("the file is bigger than {max_size} bytes"),
+      ));
+    }
+    Ok(contents)
+  };
+  let format = detect_archive_format(archive_path)?;
+  if format == ArchiveFormat::Zip {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+    for i in 0..zip.len() {
+      let mut entry = zip.by_index(i)?;
+      if entry.is_file() && entry.name().trim_start_matches("./") == file_name {
+        return Ok(Some(read_limited(&mut entry)?));
+      }
+    }
+    return Ok(None);
+  }
+  let mut tar = open_tar(archive_path, format)?;
+  for entry in tar.entries()? {
+    let mut entry = entry?;
+    if entry.header().entry_type().is_file()
+      && entry.path()?.to_string_lossy().trim_start_matches("./") == file_name
+    {
+      return Ok(Some(read_limited(&mut entry)?));
+    }
+  }
+  Ok(None)
+}
+
+// A file on disk to add to a new archive, `name` always uses `/` as the separator so archives
+// made on Windows extract properly elsewhere
+pub struct ArchiveFile {
+  pub source: PathBuf,
+  pub name: String,
+  pub executable: bool,
+}
+
+impl ArchiveFile {
+  fn mode(&self) -> u32 {
+    if self.executable {
+      0o755
+    } else {
+      0o644
+    }
+  }
+}
+
+fn write_zip(archive: File, files: &[ArchiveFile]) -> Result<(), ArchiveError> {
+  let mut zip = zip::ZipWriter::new(BufWriter::with_capacity(EXTRACT_BUFFER_SIZE, archive));
+  for file in files {
+    let options = zip::write::SimpleFileOptions::default()
+      .compression_method(zip::CompressionMethod::Deflated)
+      .unix_permissions(file.mode())
+      .large_file(file.source.metadata()?.len() >= u32::MAX as u64);
+    zip.start_file(file.name.as_str(), options)?;
+    std::io::copy(&mut File::open(&file.source)?, &mut zip)?;
+  }
+  zip.finish()?.flush()?;
+  Ok(())
+}
+
+fn write_tar<W: Write>(writer: W, files: &[ArchiveFile]) -> Result<W, ArchiveError> {
+  let mut tar = tar::Builder::new(writer);
+  for file in files {
+    let metadata = file.source.metadata()?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(metadata.len());
+    header.set_mode(file.mode());
+    header.set_mtime(
+      metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_secs()),
+    );
+    tar.append_data(&mut header, &file.name, File::open(&file.source)?)?;
+  }
+  Ok(tar.into_inner()?)
+}
+
+pub fn create_archive(
+  archive_path: &Path,
+  format: ArchiveFormat,
+  files: &[ArchiveFile],
+) -> Result<(), ArchiveError> {
+  log::info!(
+    "creating {} with {} files",
+    archive_path.display(),
+    files.len()
+  );
+  let archive = File::create(archive_path)?;
+  match format {
+    ArchiveFormat::Zip => write_zip(archive, files)?,
+    ArchiveFormat::TarGz => {
+      write_tar(
+        flate2::write::GzEncoder::new(archive, flate2::Compression::default()),
+        files,
+      )?
+      .finish()?
+      .flush()?;
+    }
+    ArchiveFormat::TarXz => {
+      write_tar(xz2::write::XzEncoder::new(archive, 6), files)?
+        .finish()?
+        .flush()?;
+    }
+    ArchiveFormat::TarZst => {
+      write_tar(zstd::stream::write::Encoder::new(archive, 0)?, files)?
+        .finish()?
+        .flush()?;
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use tar::{EntryType, Header};
+  use zip::write::SimpleFileOptions;
+
+  enum TarEntry<'a> {
+    File(&'a str, &'a [u8]),
+    Symlink(&'a str, &'a str),
+    HardLink(&'a str, &'a str),
+  }
+
+  // `tar` refuses to write unsafe paths, so the name is set in the header directly
+  fn set_raw_path(header: &mut Header, path: &str) {
+    let name = &mut header.as_old_mut().name;
+    name.fill(0);
+    name[..path.len()].copy_from_slice(path.as_bytes());
+  }
+
+  fn write_tar(path: &Path, entries: &[TarEntry]) {
+    let file = File::create(path).unwrap();
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+      file,
+      flate2::Compression::fast(),
+    ));
+    for entry in entries {
+      let mut header = Header::new_gnu();
+      header.set_mode(0o644);
+      let (name, data): (&str, &[u8]) = match entry {
+        TarEntry::File(name, data) => {
+          header.set_entry_type(EntryType::Regular);
+          (name, data)
+        }
+        TarEntry::Symlink(name, target) => {
+          header.set_entry_type(EntryType::Symlink);
+          header.set_link_name(target).unwrap();
+          (name, &[])
+        }
+        TarEntry::HardLink(name, target) => {
+          header.set_entry_type(EntryType::Link);
+          header.set_link_name(target).unwrap();
+          (name, &[])
+        }
+      };
+      set_raw_path(&mut header, name);
+      header.set_size(data.len() as u64);
+      header.set_cksum();
+      builder.append(&header, data).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+  }
+
+  enum ZipEntry<'a> {
+    File(&'a str, &'a [u8]),
+    Symlink(&'a str, &'a str),
+  }
+
+  fn write_zip(path: &Path, entries: &[ZipEntry]) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    let options = SimpleFileOptions::default();
+    for entry in entries {
+      match entry {
+        ZipEntry::File(name, data) => {
+          zip.start_file(*name, options).unwrap();
+          zip.write_all(data).unwrap();
+        }
+        ZipEntry::Symlink(name, target) => zip.add_symlink(*name, *target, options).unwrap(),
+      }
+    }
+    zip.finish().unwrap();
+  }
+
+  // the archive and a destination nested one level down, so escapes land in `root`
+  fn setup() -> (tempfile::TempDir, PathBuf) {
+    let root = tempfile::tempdir().unwrap();
+    let destination = root.path().join("out");
+    std::fs::create_dir(&destination).unwrap();
+    (root, destination)
+  }
+
+  fn extract(archive: &Path, destination: &Path) -> Result<ArchiveFormat, ArchiveError> {
+    extract_archive(archive, destination, false, &mut |_| {})
+  }
+
+  fn assert_unsafe(result: Result<ArchiveFormat, ArchiveError>) {
+    match result {
+      Err(ArchiveError::UnsafeEntry { .. }) => {}
+      other => panic!("expected an unsafe entry error, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn extracts_files_and_links_inside_the_archive() {
+    let (root, destination) = setup();
+    let archive = root.path().join("mod.tar.gz");
+    write_tar(
+      &archive,
+      &[
+        TarEntry::File("data/goal_src/game.gp", b"goal"),
+        TarEntry::Symlink("data/link", "goal_src/game.gp"),
+        TarEntry::HardLink("game.gp", "data/goal_src/game.gp"),
+      ],
+    );
+    extract(&archive, &destination).unwrap();
+    assert_eq!(
+      std::fs::read(destination.join("data/link")).unwrap(),
+      b"goal"
+    );
+    assert_eq!(std::fs::read(destination.join("game.gp")).unwrap(), b"goal");
+  }
+
+  #[test]
+  fn rejects_parent_dir_entries() {
+    let (root, destination) = setup();
+    let tar_path = root.path().join("mod.tar.gz");
+    write_tar(&tar_path, &[TarEntry::File("../evil", b"x")]);
+    assert_unsafe(extract(&tar_path, &destination));
+
+    let zip_path = root.path().join("mod.zip");
+    write_zip(&zip_path, &[ZipEntry::File("../evil", b"x")]);
+    assert_unsafe(extract(&zip_path, &destination));
+    assert!(!root.path().join("evil").exists());
+  }
+
+  #[test]
+  fn rejects_absolute_entries() {
+    let (root, destination) = setup();
+    let evil = root.path().join("evil");
+    let evil = evil.to_str().unwrap();
+    let tar_path = root.path().join("mod.tar.gz");
+    write_tar(&tar_path, &[TarEntry::File(evil, b"x")]);
+    assert_unsafe(extract(&tar_path, &destination));
+
+    let zip_path = root.path().join("mod.zip");
+    write_zip(&zip_path, &[ZipEntry::File(evil, b"x")]);
+    assert_unsafe(extract(&zip_path, &destination));
+    assert!(!root.path().join("evil").exists());
+  }
+
+  #[test]
+  fn rejects_writing_through_symlink_chains() {
+    let (root, destination) = setup();
+    // each link stays inside on its own, together `p/d` points at the parent of the destination
+    let tar_path = root.path().join("mod.tar.gz");
+    write_tar(
+      &tar_path,
+      &[
+        TarEntry::Symlink("p/sub", ".."),
+        TarEntry::Symlink("p/d", "sub/.."),
+        TarEntry::File("p/d/x", b"x"),
+      ],
+    );
+    assert_unsafe(extract(&tar_path, &destination));
+    assert!(!root.path().join("x").exists());
+
+    let (root, destination) = setup();
+    let zip_path = root.path().join("mod.zip");
+    write_zip(
+      &zip_path,
+      &[
+        ZipEntry::Symlink("p/sub", ".."),
+        ZipEntry::Symlink("p/d", "sub/.."),
+        ZipEntry::File("p/d/x", b"x"),
+      ],
+    );
+    assert_unsafe(extract(&zip_path, &destination));
+    assert!(!root.path().join("x").exists());
+  }
+
+  #[test]
+  fn rejects_escaping_symlinks() {
+    let (root, destination) = setup();
+    let tar_path = root.path().join("mod.tar.gz");
+    write_tar(&tar_path, &[TarEntry::Symlink("p/out", "../..")]);
+    assert_unsafe(extract(&tar_path, &destination));
+
+    let zip_path = root.path().join("mod.zip");
+    write_zip(&zip_path, &[ZipEntry::Symlink("p/out", "/etc")]);
+    assert_unsafe(extract(&zip_path, &destination));
+  }
+
+  #[test]
+  fn rejects_escaping_hard_links() {
+    let (root, destination) = setup();
+    std::fs::write(root.path().join("secret"), b"secret").unwrap();
+
+    let tar_path = root.path().join("absolute.tar.gz");
+    let secret = root.path().join("secret");
+    write_tar(
+      &tar_path,
+      &[TarEntry::HardLink("h", secret.to_str().unwrap())],
+    );
+    assert_unsafe(extract(&tar_path, &destination));
+
+    let tar_path = root.path().join("parent.tar.gz");
+    write_tar(&tar_path, &[TarEntry::HardLink("h", "../secret")]);
+    assert_unsafe(extract(&tar_path, &destination));
+
+    // through links that were extracted earlier
+    let tar_path = root.path().join("chain.tar.gz");
+    write_tar(
+      &tar_path,
+      &[
+        TarEntry::Symlink("p/sub", ".."),
+        TarEntry::Symlink("p/d", "sub/.."),
+        TarEntry::HardLink("h", "p/d/secret"),
+      ],
+    );
+    assert_unsafe(extract(&tar_path, &destination));
+    assert!(!destination.join("h").exists());
+  }
+}
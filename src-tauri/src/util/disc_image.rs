@@ -0,0 +1,303 @@
+// Conversion of raw CD disc images (BIN/CUE) into a plain 2048-byte sector ISO stream that the
+// extractor understands.
+//
+// Raw images store full 2352 byte sectors, for example a MODE2/2352 (XA Form 1) sector is laid out as:
+// - 12 byte sync pattern
+// - 4 byte header
+// - 8 byte sub-header
+// - 2048 bytes of user data
+// - 280 bytes of error detection / correction
+//
+// so all we have to do is pull the user data out of each sector.
+
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+};
+
+use tempfile::NamedTempFile;
+
+use super::file::create_dir;
+
+const SECTOR_SYNC: [u8; 12] = [
+  0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00,
+];
+const ISO_SECTOR_SIZE: u64 = 2048;
+const RAW_SECTOR_SIZE: u64 = 2352;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiscImageError {
+  #[error(transparent)]
+  IO(#[from] std::io::Error),
+  #[error("{0}")]
+  CueSheet(String),
+  #[error("{0}")]
+  UnsupportedImage(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorLayout {
+  pub sector_size: u64,
+  pub data_offset: u64,
+}
+
+impl SectorLayout {
+  fn from_track_mode(mode: &str) -> Option<SectorLayout> {
+    let (sector_size, data_offset) = match mode.to_uppercase().as_str() {
+      "MODE1/2048" | "MODE2/2048" => (2048, 0),
+      "MODE1/2352" => (2352, 16),
+      "MODE2/2352" => (2352, 24),
+      "MODE2/2336" => (2336, 8),
+      _ => return None,
+    };
+    Some(SectorLayout {
+      sector_size,
+      data_offset,
+    })
+  }
+
+  fn is_plain_iso(&self) -> bool {
+    self.sector_size == ISO_SECTOR_SIZE
+  }
+}
+
+#[derive(Debug)]
+pub struct DataTrack {
+  pub bin_path: PathBuf,
+  pub layout: SectorLayout,
+  pub start_sector: u64,
+  pub end_sector: Option<u64>,
+}
+
+pub fn is_bin_cue_image(path: &Path) -> bool {
+  match path.extension().and_then(|ext| ext.to_str()) {
+    Some(ext) => ext.eq_ignore_ascii_case("cue") || ext.eq_ignore_ascii_case("bin"),
+    None => false,
+  }
+}
+
+fn msf_to_sector(msf: &str) -> Option<u64> {
+  let parts: Vec<u64> = msf
+    .split(':')
+    .map(|part| part.parse::<u64>())
+    .collect::<Result<_, _>>()
+    .ok()?;
+  match parts.as_slice() {
+    [minutes, seconds, frames] => Some((minutes * 60 + seconds) * 75 + frames),
+    _ => None,
+  }
+}
+
+// Splits a cue sheet line into tokens, keeping quoted file names intact
+fn tokenize_cue_line(line: &str) -> Vec<String> {
+  let mut tokens = Vec::new();
+  let mut current = String::new();
+  let mut in_quotes = false;
+  for c in line.trim().chars() {
+    match c {
+      '"' => in_quotes = !in_quotes,
+      c if c.is_whitespace() && !in_quotes => {
+        if !current.is_empty() {
+          tokens.push(std::mem::take(&mut current));
+        }
+      }
+      c => current.push(c),
+    }
+  }
+  if !current.is_empty() {
+    tokens.push(current);
+  }
+  tokens
+}
+
+// Finds the first data track in the cue sheet, the game's file system always lives there
+pub fn parse_cue_sheet(cue_path: &Path) -> Result<DataTrack, DiscImageError> {
+  let contents = std::fs::read_to_string(cue_path)?;
+  let cue_dir = cue_path.parent().unwrap_or(Path::new(""));
+
+  let mut current_file: Option<PathBuf> = None;
+  let mut data_track: Option<DataTrack> = None;
+  let mut in_data_track = false;
+  for line in contents.lines() {
+    let tokens = tokenize_cue_line(line);
+    let Some(keyword) = tokens.first() else {
+      continue;
+    };
+    match keyword.to_uppercase().as_str() {
+      "FILE" => {
+        if data_track.is_some() {
+          // the data track ends with its file
+          break;
+        }
+        current_file = tokens.get(1).map(|name| cue_dir.join(name));
+      }
+      "TRACK" => {
+        if let Some(track) = &mut data_track {
+          in_data_track = false;
+          if track.end_sector.is_none() {
+            // the next track's INDEX 01 will tell us where this one stops
+            track.end_sector = Some(u64::MAX);
+          }
+          continue;
+        }
+        let mode = tokens.get(2).map(String::as_str).unwrap_or("");
+        if let Some(layout) = SectorLayout::from_track_mode(mode) {
+          let bin_path = current_file.clone().ok_or_else(|| {
+            DiscImageError::CueSheet("Cue sheet has a TRACK before any FILE".to_owned())
+          })?;
+          data_track = Some(DataTrack {
+            bin_path,
+            layout,
+            start_sector: 0,
+            end_sector: None,
+          });
+          in_data_track = true;
+        }
+      }
+      "INDEX" => {
+        let index = tokens.get(1).map(String::as_str);
+        let sector = tokens.get(2).and_then(|msf| msf_to_sector(msf));
+        if let (Some(index), Some(sector), Some(track)) = (index, sector, &mut data_track) {
+          if in_data_track {
+            if index == "01" {
+              track.start_sector = sector;
+            }
+          } else if track.end_sector == Some(u64::MAX) {
+            // the following track's first index (including any pregap) ends the data track
+            track.end_sector = Some(sector);
+            break;
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  let mut track = data_track.ok_or_else(|| {
+    DiscImageError::CueSheet(format!(
+      "No supported data track found in {}",
+      cue_path.display()
+    ))
+  })?;
+  if track.end_sector == Some(u64::MAX) {
+    track.end_sector = None;
+  }
+  if !track.bin_path.exists() {
+    return Err(DiscImageError::CueSheet(format!(
+      "Cue sheet references '{}' which does not exist",
+      track.bin_path.display()
+    )));
+  }
+  Ok(track)
+}
+
+// Without a cue sheet, look at the first sector to figure out how the image is laid out
+pub fn detect_bin_layout(bin_path: &Path) -> Result<SectorLayout, DiscImageError> {
+  let length = std::fs::metadata(bin_path)?.len();
+  let mut header = [0u8; 16];
+  let mut file = File::open(bin_path)?;
+  let has_sync = file.read_exact(&mut header).is_ok() && header[..12] == SECTOR_SYNC;
+  if has_sync && length % RAW_SECTOR_SIZE == 0 {
+    return match header[15] {
+      1 => Ok(SectorLayout::from_track_mode("MODE1/2352").unwrap()),
+      2 => Ok(SectorLayout::from_track_mode("MODE2/2352").unwrap()),
+      mode => Err(DiscImageError::UnsupportedImage(format!(
+        "Unsupported sector mode {mode} in {}",
+        bin_path.display()
+      ))),
+    };
+  }
+  if length % ISO_SECTOR_SIZE == 0 {
+    return Ok(SectorLayout::from_track_mode("MODE1/2048").unwrap());
+  }
+  Err(DiscImageError::UnsupportedImage(format!(
+    "Unable to determine the sector layout of {}",
+    bin_path.display()
+  )))
+}
+
+fn copy_track_user_data(track: &DataTrack, output: &mut impl Write) -> Result<(), DiscImageError> {
+  let layout = track.layout;
+  let mut input = BufReader::with_capacity(
+    (layout.sector_size * 256) as usize,
+    File::open(&track.bin_path)?,
+  );
+  input.seek(SeekFrom::Start(track.start_sector * layout.sector_size))?;
+
+  let mut sector = vec![0u8; layout.sector_size as usize];
+  let data_range = layout.data_offset as usize..(layout.data_offset + ISO_SECTOR_SIZE) as usize;
+  let mut current_sector = track.start_sector;
+  while track.end_sector.is_none_or(|end| current_sector < end) {
+    match input.read_exact(&mut sector) {
+      Ok(_) => {}
+      Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => break,
+      Err(err) => return Err(err.into()),
+    }
+    output.write_all(&sector[data_range.clone()])?;
+    current_sector += 1;
+  }
+  output.flush()?;
+  Ok(())
+}
+
+// An image that is ready to hand to the extractor, converted images live in a temporary file that
+// is deleted as soon as this is dropped, so hold onto it until the extractor is done with it
+pub enum PreparedDiscImage {
+  Plain(PathBuf),
+  Converted(NamedTempFile),
+}
+
+impl PreparedDiscImage {
+  pub fn path(&self) -> &Path {
+    match self {
+      PreparedDiscImage::Plain(path) => path,
+      PreparedDiscImage::Converted(file) => file.path(),
+    }
+  }
+}
+
+// Converts a `.cue` sheet or a single-track `.bin` into a temporary ISO inside `temp_dir`, unless
+// the data track is already made up of plain 2048 byte sectors and can be used as-is
+pub fn prepare_disc_image(
+  image_path: &Path,
+  temp_dir: &PathBuf,
+) -> Result<PreparedDiscImage, DiscImageError> {
+  let is_cue = image_path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"));
+  let track = if is_cue {
+    parse_cue_sheet(image_path)?
+  } else {
+    DataTrack {
+      bin_path: image_path.to_path_buf(),
+      layout: detect_bin_layout(image_path)?,
+      start_sector: 0,
+      end_sector: None,
+    }
+  };
+  if track.layout.is_plain_iso() && track.start_sector == 0 && track.end_sector.is_none() {
+    log::info!(
+      "{} is already a 2048 byte sector image, no conversion needed",
+      track.bin_path.display()
+    );
+    return Ok(PreparedDiscImage::Plain(track.bin_path));
+  }
+
+  create_dir(temp_dir)?;
+  let temp_iso = tempfile::Builder::new()
+    .prefix("disc-image-")
+    .suffix(".iso")
+    .tempfile_in(temp_dir)?;
+  log::info!(
+    "Converting {} ({:?}) to {}",
+    track.bin_path.display(),
+    track.layout,
+    temp_iso.path().display()
+  );
+  let mut output = BufWriter::new(temp_iso.as_file());
+  copy_track_user_data(&track, &mut output)?;
+  drop(output);
+  Ok(PreparedDiscImage::Converted(temp_iso))
+}
@@ -2,13 +2,15 @@
 #![allow(dead_code)]
 extern crate rustc_serialize;
 
+use rev_buf_reader::RevBufReader;
 use rustc_serialize::base64::{ToBase64, MIME};
-use rustc_serialize::hex::ToHex;
+use sha2::{Digest, Sha256};
 use std::{
   fs::File,
-  io::Read,
+  io::{BufRead, Read},
   path::{Path, PathBuf},
 };
+use walkdir::WalkDir;
 
 pub fn delete_dir<T: AsRef<Path>>(path: T) -> Result<(), std::io::Error> {
   if path.as_ref().exists() && path.as_ref().is_dir() {
@@ -25,6 +27,47 @@ pub fn create_dir(path: &PathBuf) -> Result<(), std::io::Error> {
   Ok(())
 }
 
+// Links `link` to the `target` folder, as a junction on Windows since symlinks need special
+// privileges there
+#[cfg(unix)]
+pub fn link_dir(target: &Path, link: &Path) -> Result<(), std::io::Error> {
+  std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+pub fn link_dir(target: &Path, link: &Path) -> Result<(), std::io::Error> {
+  use std::os::windows::process::CommandExt;
+
+  let status = std::process::Command::new("cmd")
+    .arg("/C")
+    .arg("mklink")
+    .arg("/J")
+    .arg(link)
+    .arg(target)
+    .creation_flags(0x08000000)
+    .status()?;
+  if !status.success() {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::Other,
+      format!("Unable to link {} to {}", link.display(), target.display()),
+    ));
+  }
+  Ok(())
+}
+
+// Removes a link made by `link_dir`, leaving what it points to alone
+pub fn remove_dir_link(link: &Path) -> Result<(), std::io::Error> {
+  if cfg!(windows) {
+    std::fs::remove_dir(link)
+  } else {
+    std::fs::remove_file(link)
+  }
+}
+
+pub fn is_link(path: &Path) -> bool {
+  std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
 pub fn delete_file(path: &PathBuf) -> Result<(), std::io::Error> {
   if path.exists() && path.is_file() {
     std::fs::remove_file(path)?;
@@ -43,10 +86,60 @@ pub fn overwrite_dir(src: &PathBuf, dst: &PathBuf) -> Result<(), fs_extra::error
   Ok(())
 }
 
+pub fn hash_file_contents(hasher: &mut Sha256, path: &Path) -> Result<(), std::io::Error> {
+  let mut file = File::open(path)?;
+  let mut buffer = vec![0; 1024 * 1024];
+  loop {
+    let read = file.read(&mut buffer)?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+  }
+  Ok(())
+}
+
+// Hashes every file in the directory (including their relative paths) in a stable order,
+// so two directories with identical contents hash the same regardless of where they live
+pub fn hash_dir_contents(hasher: &mut Sha256, dir: &Path) -> Result<(), std::io::Error> {
+  if !dir.exists() {
+    return Ok(());
+  }
+  let mut files: Vec<PathBuf> = WalkDir::new(dir)
+    .into_iter()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.path().is_file())
+    .map(|e| e.into_path())
+    .collect();
+  files.sort();
+  for file in files {
+    let relative_path = file.strip_prefix(dir).unwrap_or(&file);
+    hasher.update(
+      relative_path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .as_bytes(),
+    );
+    hash_file_contents(hasher, &file)?;
+  }
+  Ok(())
+}
+
 pub fn read_lines_in_file(path: &PathBuf) -> Result<String, Box<dyn std::error::Error>> {
   Ok(std::fs::read_to_string(path)?)
 }
 
+// Reads the last `count` lines of the file, without having to read the entire thing
+pub fn read_last_lines(path: &Path, count: usize) -> Result<Vec<String>, std::io::Error> {
+  let reader = RevBufReader::new(File::open(path)?);
+  let mut lines = reader
+    .lines()
+    .take(count)
+    .collect::<Result<Vec<String>, _>>()?;
+  lines.reverse();
+  Ok(lines)
+}
+
 pub fn touch_file(path: &PathBuf) -> std::io::Result<()> {
   match std::fs::OpenOptions::new()
     .create(true)
@@ -59,26 +152,37 @@ pub fn touch_file(path: &PathBuf) -> std::io::Result<()> {
   }
 }
 
-pub fn get_image_file_type(hex: &str) -> &str {
-  if hex.starts_with("ffd8ffe0") {
-    return "jpeg";
-  } else if hex.starts_with("89504e47") {
-    return "png";
-  } else if hex.starts_with("47494638") {
-    return "gif";
+// The type of image from its first few bytes, `None` for anything we don't display
+pub fn get_image_file_type(contents: &[u8]) -> Option<&'static str> {
+  if contents.starts_with(&[0xff, 0xd8, 0xff]) {
+    Some("jpeg")
+  } else if contents.starts_with(&[0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]) {
+    Some("png")
+  } else if contents.starts_with(b"GIF87a") || contents.starts_with(b"GIF89a") {
+    Some("gif")
+  } else if contents.len() >= 12 && contents.starts_with(b"RIFF") && &contents[8..12] == b"WEBP" {
+    Some("webp")
+  } else {
+    None
   }
-  panic!("invalid file type")
 }
 
+// A data url for the image, empty if it can't be read or isn't an image
 pub fn to_image_base64(path: &str) -> String {
-  let mut file = File::open(path).unwrap();
-  let mut vec = Vec::new();
-  let _ = file.read_to_end(&mut vec);
-  let base64 = vec.to_base64(MIME);
-  let hex = vec.to_hex();
-  format!(
-    "data:image/{};base64,{}",
-    get_image_file_type(&hex),
-    base64.replace("\r\n", "")
-  )
+  let mut contents = Vec::new();
+  if let Err(err) = File::open(path).and_then(|mut file| file.read_to_end(&mut contents)) {
+    log::warn!("Unable to read image {}: {}", path, err);
+    return "".to_owned();
+  }
+  match get_image_file_type(&contents) {
+    Some(file_type) => format!(
+      "data:image/{};base64,{}",
+      file_type,
+      contents.to_base64(MIME).replace("\r\n", "")
+    ),
+    None => {
+      log::warn!("{} is not a supported image", path);
+      "".to_owned()
+    }
+  }
 }
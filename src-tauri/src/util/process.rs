@@ -1,4 +1,9 @@
-use std::process::ExitStatus;
+use std::{
+  io::{Read, Write},
+  path::{Path, PathBuf},
+  process::ExitStatus,
+  sync::{Arc, Mutex},
+};
 
 use tokio::{
   io::{AsyncBufReadExt, AsyncWriteExt},
@@ -10,11 +15,167 @@ use crate::commands::CommandError;
 use super::file::create_dir;
 use tauri::{Emitter, Manager};
 
+// No log grows past this, logs that are appended to are rotated once they reach it
+const MAX_LOG_SIZE: u64 = 25 * 1024 * 1024;
+const LOG_LIMIT_NOTICE: &str =
+  "\n[the log reached its size limit, the rest of the output was dropped]\n";
+
+// How much more can be written to a log, a process that floods its output can't fill the disk
+struct LogLimit {
+  written: u64,
+  reached: bool,
+}
+
+impl LogLimit {
+  fn new(existing_size: u64) -> LogLimit {
+    LogLimit {
+      written: existing_size,
+      reached: false,
+    }
+  }
+
+  // The part of `data` that still fits, and whether the notice has to be written after it because
+  // this is where the limit was reached
+  fn accept<'a>(&mut self, data: &'a [u8]) -> (&'a [u8], bool) {
+    if self.reached {
+      return (&[], false);
+    }
+    let remaining = MAX_LOG_SIZE.saturating_sub(self.written);
+    if (data.len() as u64) <= remaining {
+      self.written += data.len() as u64;
+      return (data, false);
+    }
+    self.reached = true;
+    self.written = MAX_LOG_SIZE;
+    (&data[..remaining as usize], true)
+  }
+}
+
+// A log written from the launcher, writes past the size limit are dropped
+pub struct LogFile {
+  file: tokio::fs::File,
+  limit: LogLimit,
+}
+
+impl LogFile {
+  pub async fn write_all(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
+    let (accepted, reached_limit) = self.limit.accept(data);
+    self.file.write_all(accepted).await?;
+    if reached_limit {
+      self.file.write_all(LOG_LIMIT_NOTICE.as_bytes()).await?;
+    }
+    Ok(())
+  }
+
+  pub async fn flush(&mut self) -> Result<(), std::io::Error> {
+    self.file.flush().await
+  }
+}
+
+// A log that a process' output is copied into, writes past the size limit are dropped
+pub struct SizeLimitedWriter<W: Write> {
+  inner: W,
+  limit: LogLimit,
+}
+
+impl<W: Write> Write for SizeLimitedWriter<W> {
+  fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+    let (accepted, reached_limit) = self.limit.accept(data);
+    self.inner.write_all(accepted)?;
+    if reached_limit {
+      self.inner.write_all(LOG_LIMIT_NOTICE.as_bytes())?;
+    }
+    // everything is reported as written, so the process' output keeps being drained
+    Ok(data.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    self.inner.flush()
+  }
+}
+
+fn rotated_log_name(name: &str, index: usize) -> String {
+  let stem = name.strip_suffix(".log").unwrap_or(name);
+  format!("{stem}.{index}.log")
+}
+
+// Shifts `<name>.log` to `<name>.1.log`, `<name>.1.log` to `<name>.2.log` and so on, keeping at
+// most `retention` previous logs around
+pub fn rotate_log_file(log_dir: &Path, name: &str, retention: usize) -> Result<(), std::io::Error> {
+  let oldest = log_dir.join(rotated_log_name(name, retention.max(1)));
+  if oldest.exists() {
+    std::fs::remove_file(oldest)?;
+  }
+  for index in (1..retention).rev() {
+    let rotated = log_dir.join(rotated_log_name(name, index));
+    if rotated.exists() {
+      std::fs::rename(rotated, log_dir.join(rotated_log_name(name, index + 1)))?;
+    }
+  }
+  let current = log_dir.join(name);
+  if current.exists() {
+    if retention == 0 {
+      std::fs::remove_file(current)?;
+    } else {
+      std::fs::rename(current, log_dir.join(rotated_log_name(name, 1)))?;
+    }
+  }
+  Ok(())
+}
+
+// A fresh log starts a new session so the previous one is rotated out, logs that are appended
+// to are only rotated once they grow too large
+fn rotate_if_needed(
+  log_dir: &Path,
+  name: &str,
+  append: bool,
+  retention: usize,
+) -> Result<(), std::io::Error> {
+  let current = log_dir.join(name);
+  let too_large = std::fs::metadata(&current)
+    .map(|metadata| metadata.len() >= MAX_LOG_SIZE)
+    .unwrap_or(false);
+  if current.exists() && (!append || too_large) {
+    rotate_log_file(log_dir, name, retention)?;
+  }
+  Ok(())
+}
+
+// Keeps the newest `retention` files in the directory, relies on the file names sorting by date
+// Only the dated app logs written by fern are pruned here, game and extractor logs are kept in
+// check by `rotate_log_file`. Today's log is still open so it is never removed.
+pub fn prune_log_dir(log_dir: &Path, retention: usize) -> Result<(), std::io::Error> {
+  let today = chrono::Local::now().date_naive();
+  let mut dated_logs: Vec<(chrono::NaiveDate, PathBuf)> = std::fs::read_dir(log_dir)?
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| path.is_file())
+    .filter_map(|path| {
+      let name = path.file_name()?.to_str()?;
+      let date = chrono::NaiveDate::parse_from_str(name.strip_suffix(".log")?, "%Y-%m-%d").ok()?;
+      Some((date, path))
+    })
+    .collect();
+  dated_logs.sort();
+  dated_logs.reverse();
+  for (date, path) in dated_logs.into_iter().skip(retention) {
+    if date == today {
+      continue;
+    }
+    log::info!("deleting - {}", path.display());
+    if let Err(err) = std::fs::remove_file(&path) {
+      log::warn!("Unable to delete old log {}: {}", path.display(), err);
+    }
+  }
+  Ok(())
+}
+
 pub async fn create_log_file(
   app_handle: &tauri::AppHandle,
   name: String,
   append: bool,
-) -> Result<tokio::fs::File, CommandError> {
+  retention: usize,
+) -> Result<LogFile, CommandError> {
   let log_path = &match app_handle.path().app_log_dir() {
     Ok(path) => path,
     Err(_) => {
@@ -24,6 +185,7 @@ pub async fn create_log_file(
     }
   };
   create_dir(log_path)?;
+  rotate_if_needed(log_path, &name, append, retention)?;
   let mut file_options = tokio::fs::OpenOptions::new();
   file_options.read(true);
   file_options.create(true);
@@ -33,7 +195,11 @@ pub async fn create_log_file(
     file_options.write(true).truncate(true);
   }
   let file = file_options.open(log_path.join(name)).await?;
-  Ok(file)
+  let existing_size = file.metadata().await?.len();
+  Ok(LogFile {
+    file,
+    limit: LogLimit::new(existing_size),
+  })
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -42,7 +208,7 @@ struct LogPayload {
 }
 
 pub async fn watch_process(
-  log_file: &mut tokio::fs::File,
+  log_file: &mut LogFile,
   child: &mut tokio::process::Child,
   app_handle: &tauri::AppHandle,
 ) -> Result<ExitStatus, CommandError> {
@@ -90,11 +256,20 @@ pub async fn watch_process(
   Ok(process_status)
 }
 
+pub fn get_log_file_path(app_handle: &tauri::AppHandle, name: &str) -> Option<String> {
+  app_handle
+    .path()
+    .app_log_dir()
+    .ok()
+    .map(|log_dir| log_dir.join(name).to_string_lossy().into_owned())
+}
+
 pub fn create_std_log_file(
   app_handle: &tauri::AppHandle,
   name: String,
   append: bool,
-) -> Result<std::fs::File, CommandError> {
+  retention: usize,
+) -> Result<SizeLimitedWriter<std::fs::File>, CommandError> {
   let log_path = &match app_handle.path().app_log_dir() {
     Ok(path) => path,
     Err(_) => {
@@ -104,6 +279,7 @@ pub fn create_std_log_file(
     }
   };
   create_dir(log_path)?;
+  rotate_if_needed(log_path, &name, append, retention)?;
   let mut file_options = std::fs::OpenOptions::new();
   file_options.create(true);
   if append {
@@ -112,5 +288,94 @@ pub fn create_std_log_file(
     file_options.write(true).truncate(true);
   }
   let file = file_options.open(log_path.join(name))?;
-  Ok(file)
+  let existing_size = file.metadata()?.len();
+  Ok(SizeLimitedWriter {
+    inner: file,
+    limit: LogLimit::new(existing_size),
+  })
+}
+
+// Copies the process' piped stdout and stderr into the log until the process closes them
+pub fn pipe_output_to_log(child: &mut std::process::Child, log: SizeLimitedWriter<std::fs::File>) {
+  let log = Arc::new(Mutex::new(log));
+  let outputs: [Option<Box<dyn Read + Send>>; 2] = [
+    child
+      .stdout
+      .take()
+      .map(|stdout| Box::new(stdout) as Box<dyn Read + Send>),
+    child
+      .stderr
+      .take()
+      .map(|stderr| Box::new(stderr) as Box<dyn Read + Send>),
+  ];
+  for mut output in outputs.into_iter().flatten() {
+    let log = log.clone();
+    std::thread::spawn(move || {
+      let mut buffer = [0; 8192];
+      let mut log_failed = false;
+      loop {
+        let read = match output.read(&mut buffer) {
+          Ok(0) | Err(_) => break,
+          Ok(read) => read,
+        };
+        // the output keeps being drained even when the log can't be written, or the process
+        // would block once the pipe is full
+        if log_failed {
+          continue;
+        }
+        let result = match log.lock() {
+          Ok(mut log_writer) => log_writer
+            .write_all(&buffer[..read])
+            .and_then(|_| log_writer.flush()),
+          Err(_) => Ok(()),
+        };
+        if let Err(err) = result {
+          log::error!("Unable to write process output to its log: {}", err);
+          log_failed = true;
+        }
+      }
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn prunes_only_old_app_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let today = format!("{}.log", chrono::Local::now().format("%Y-%m-%d"));
+    let names = [
+      "2020-01-01.log",
+      "2020-01-02.log",
+      "2020-01-03.log",
+      today.as_str(),
+      "game-jak1.log",
+      "game-jak1.1.log",
+      "extractor-jak2.log",
+    ];
+    for name in names {
+      std::fs::write(dir.path().join(name), "").unwrap();
+    }
+    prune_log_dir(dir.path(), 2).unwrap();
+    let mut remaining: Vec<String> = std::fs::read_dir(dir.path())
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+      .collect();
+    remaining.sort();
+    let mut expected = vec![
+      "2020-01-03.log".to_owned(),
+      today.clone(),
+      "extractor-jak2.log".to_owned(),
+      "game-jak1.1.log".to_owned(),
+      "game-jak1.log".to_owned(),
+    ];
+    expected.sort();
+    assert_eq!(remaining, expected);
+
+    prune_log_dir(dir.path(), 0).unwrap();
+    assert!(dir.path().join(&today).exists());
+    assert!(!dir.path().join("2020-01-03.log").exists());
+  }
 }
@@ -0,0 +1,104 @@
+// Finds a terminal emulator to run external tools (like the REPL) in on Linux
+//
+// There is no single reliable way to open a terminal, so we try the user's preference first, then
+// `$TERMINAL`, then `xdg-terminal-exec` and finally a list of well known emulators.  Each
+// emulator has its own way of being told which program to execute.
+
+use std::{
+  env,
+  path::{Path, PathBuf},
+  process::Command,
+};
+
+use serde::Serialize;
+
+// Emulators we know how to launch, in the order they are tried, along with the arguments that
+// go before the program to execute
+const KNOWN_TERMINALS: [(&str, &[&str]); 6] = [
+  ("xdg-terminal-exec", &[]),
+  ("konsole", &["-e"]),
+  ("gnome-terminal", &["--"]),
+  ("kitty", &[]),
+  ("alacritty", &["-e"]),
+  ("xterm", &["-e"]),
+];
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Terminal {
+  pub name: String,
+  pub path: PathBuf,
+  pub exec_args: Vec<String>,
+}
+
+impl Terminal {
+  // Builds the command that opens the terminal running `program`
+  pub fn command(&self, program: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(&self.path);
+    command.args(&self.exec_args).arg(program).args(args);
+    command
+  }
+}
+
+fn exec_args_for(name: &str) -> Vec<String> {
+  KNOWN_TERMINALS
+    .iter()
+    .find(|(known, _)| *known == name)
+    .map(|(_, args)| args.iter().map(|arg| arg.to_string()).collect())
+    // most other emulators follow xterm's lead
+    .unwrap_or_else(|| vec!["-e".to_owned()])
+}
+
+fn find_executable(name: &str) -> Option<PathBuf> {
+  let candidate = Path::new(name);
+  if candidate.components().count() > 1 {
+    return candidate.is_file().then(|| candidate.to_path_buf());
+  }
+  env::var_os("PATH").and_then(|paths| {
+    env::split_paths(&paths)
+      .map(|dir| dir.join(name))
+      .find(|path| path.is_file())
+  })
+}
+
+// `terminal` is either the name of an emulator on the PATH, or the full path to one
+fn lookup_terminal(terminal: &str) -> Option<Terminal> {
+  let terminal = terminal.trim();
+  if terminal.is_empty() {
+    return None;
+  }
+  let path = find_executable(terminal)?;
+  let name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_else(|| terminal.to_owned());
+  Some(Terminal {
+    exec_args: exec_args_for(&name),
+    name,
+    path,
+  })
+}
+
+// Every known emulator that's installed
+pub fn available_terminals() -> Vec<Terminal> {
+  KNOWN_TERMINALS
+    .iter()
+    .filter_map(|(name, _)| lookup_terminal(name))
+    .collect()
+}
+
+pub fn resolve_terminal(preferred: Option<&str>) -> Option<Terminal> {
+  if let Some(preferred) = preferred {
+    match lookup_terminal(preferred) {
+      Some(terminal) => return Some(terminal),
+      None => log::warn!("Preferred terminal '{}' could not be found", preferred),
+    }
+  }
+  if let Some(terminal) = env::var("TERMINAL")
+    .ok()
+    .and_then(|terminal| lookup_terminal(&terminal))
+  {
+    return Some(terminal);
+  }
+  available_terminals().into_iter().next()
+}
@@ -1,5 +1,4 @@
-use log::info;
-use std::io::{BufReader, Cursor};
+use std::io::BufReader;
 use std::path::PathBuf;
 use std::{
   fs::File,
@@ -93,45 +92,6 @@ pub fn append_file_to_zip(
   Ok(())
 }
 
-pub fn extract_zip_file(
-  zip_path: &PathBuf,
-  extract_dir: &Path,
-  strip_top_dir: bool,
-) -> Result<(), zip_extract::ZipExtractError> {
-  let archive: Vec<u8> = std::fs::read(zip_path)?;
-  zip_extract::extract(Cursor::new(archive), extract_dir, strip_top_dir)?;
-  Ok(())
-}
-
-pub fn extract_and_delete_zip_file(
-  zip_path: &PathBuf,
-  extract_dir: &Path,
-  strip_top_dir: bool,
-) -> Result<(), zip_extract::ZipExtractError> {
-  extract_zip_file(zip_path, extract_dir, strip_top_dir)?;
-  std::fs::remove_file(zip_path)?;
-  Ok(())
-}
-
-pub fn check_if_zip_contains_top_level_dir(
-  zip_path: &PathBuf,
-  expected_dir: String,
-) -> Result<bool, Box<dyn std::error::Error>> {
-  let file = File::open(zip_path)?;
-  let reader = BufReader::new(file);
-  let mut zip = zip::ZipArchive::new(reader)?;
-  for i in 0..zip.len() {
-    let file = zip.by_index(i)?;
-    // Check if the entry is a directory and has the desired folder name
-    info!("{}", file.name());
-    if file.name().starts_with(&expected_dir) {
-      return Ok(true);
-    }
-  }
-  Ok(false)
-}
-
-// TODO - identical to the above, consolidate
 pub fn check_if_zip_contains_top_level_file(
   zip_path: &PathBuf,
   expected_file: String,
@@ -54,10 +54,13 @@ export async function runCompiler(
   );
 }
 
-export async function getLaunchGameString(gameName: string): Promise<string> {
+export async function getLaunchGameString(
+  gameName: string,
+  launchProfile?: string,
+): Promise<string> {
   return await invoke_rpc(
     "get_launch_game_string",
-    { gameName },
+    { gameName, launchProfile: launchProfile ?? null },
     () => "_mirror_",
   );
 }
@@ -65,10 +68,18 @@ export async function getLaunchGameString(gameName: string): Promise<string> {
 export async function launchGame(
   gameName: string,
   inDebug: boolean,
+  launchProfile?: string,
+  allowConcurrent?: boolean,
 ): Promise<void> {
   return await invoke_rpc(
     "launch_game",
-    { gameName, inDebug, executableLocation: null },
+    {
+      gameName,
+      inDebug,
+      executableLocation: null,
+      launchProfile: launchProfile ?? null,
+      allowConcurrent: allowConcurrent ?? false,
+    },
     () => {},
     "_mirror_",
   );
@@ -82,7 +93,13 @@ export async function launchGameWithCustomExecutable(
   if (customExecutable !== null) {
     return await invoke_rpc(
       "launch_game",
-      { gameName, inDebug: false, executableLocation: customExecutable },
+      {
+        gameName,
+        inDebug: false,
+        executableLocation: customExecutable,
+        launchProfile: null,
+        allowConcurrent: false,
+      },
       () => {},
       "_mirror_",
     );
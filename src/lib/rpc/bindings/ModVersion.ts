@@ -1,8 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ModDependency } from "./ModDependency";
+import type { ModVersionSettings } from "./ModVersionSettings";
 
 export type ModVersion = {
   version: string;
   publishedDate: string;
   assets: { [key: string]: string | null };
   supportedGames: Array<string> | null;
+  dependencies: Array<ModDependency>;
+  settings: ModVersionSettings;
 };
@@ -1,3 +1,4 @@
+import type { ModInfo } from "./bindings/ModInfo";
 import type { ModSourceData } from "./bindings/ModSourceData";
 import { invoke_rpc } from "./rpc";
 
@@ -13,3 +14,53 @@ export async function getModSourcesData(): Promise<
     return val;
   });
 }
+
+// Local paths of the given mod art urls, for the ones that could be cached
+export async function getCachedModArt(
+  urls: string[],
+): Promise<Record<string, string>> {
+  return await invoke_rpc("get_cached_mod_art", { urls }, () => {
+    let val: Record<string, string> = {};
+    return val;
+  });
+}
+
+export type ModSearchSort = "newest" | "recentlyUpdated" | "name";
+
+export interface ModSearchQuery {
+  text?: string;
+  tags?: string[];
+  game?: string;
+  platform?: string;
+  // requires a game, mods are installed per game
+  installed?: boolean;
+  sort?: ModSearchSort;
+  page?: number;
+  pageSize?: number;
+}
+
+export interface ModSearchEntry {
+  sourceName: string;
+  modName: string;
+  modInfo: ModInfo;
+  installedVersion: string | null;
+  latestVersion: string | null;
+}
+
+export interface ModSearchResult {
+  mods: ModSearchEntry[];
+  total: number;
+  page: number;
+  pageSize: number;
+}
+
+export async function searchCachedMods(
+  query: ModSearchQuery,
+): Promise<ModSearchResult> {
+  return await invoke_rpc("search_cached_mods", { query }, () => ({
+    mods: [],
+    total: 0,
+    page: query.page ?? 0,
+    pageSize: query.pageSize ?? 0,
+  }));
+}
@@ -250,6 +250,58 @@ export async function getAutoUninstallOldVersions(): Promise<boolean> {
   );
 }
 
+export async function setLogRetentionCount(value: number): Promise<void> {
+  return await invoke_rpc(
+    "update_setting_value",
+    { key: "log_retention_count", val: value },
+    () => {},
+  );
+}
+
+export async function getLogRetentionCount(): Promise<number> {
+  return await invoke_rpc(
+    "get_setting_value",
+    { key: "log_retention_count" },
+    () => 5,
+  );
+}
+
+export async function setModVersionRetentionCount(
+  value: number,
+): Promise<void> {
+  return await invoke_rpc(
+    "update_setting_value",
+    { key: "mod_version_retention_count", val: value },
+    () => {},
+  );
+}
+
+export async function getModVersionRetentionCount(): Promise<number> {
+  return await invoke_rpc(
+    "get_setting_value",
+    { key: "mod_version_retention_count" },
+    () => 2,
+  );
+}
+
+export async function setPreferredTerminal(
+  terminal: string | null,
+): Promise<void> {
+  return await invoke_rpc(
+    "update_setting_value",
+    { key: "preferred_terminal", val: terminal },
+    () => {},
+  );
+}
+
+export async function getPreferredTerminal(): Promise<string | null> {
+  return await invoke_rpc(
+    "get_setting_value",
+    { key: "preferred_terminal" },
+    () => null,
+  );
+}
+
 export async function setBypassRequirements(bypass: boolean): Promise<void> {
   return await invoke_rpc(
     "update_setting_value",
@@ -308,6 +360,66 @@ export async function cleanupEnabledTexturePacks(
   );
 }
 
+export interface LaunchProfile {
+  gkArgs: string[];
+  passthroughArgs: string[];
+  envVars: Record<string, string>;
+  wrapperCommand: string[];
+  executablePath: string | null;
+}
+
+export async function getLaunchProfiles(
+  gameName: string,
+  modName?: string,
+  sourceName?: string,
+): Promise<Record<string, LaunchProfile>> {
+  return await invoke_rpc(
+    "get_launch_profiles",
+    { gameName, modName: modName ?? null, sourceName: sourceName ?? null },
+    () => ({}),
+  );
+}
+
+export async function saveLaunchProfile(
+  gameName: string,
+  profileName: string,
+  profile: LaunchProfile,
+  modName?: string,
+  sourceName?: string,
+): Promise<void> {
+  return await invoke_rpc(
+    "save_launch_profile",
+    {
+      gameName,
+      profileName,
+      profile,
+      modName: modName ?? null,
+      sourceName: sourceName ?? null,
+    },
+    () => {},
+    "Unable to save launch profile",
+  );
+}
+
+export async function deleteLaunchProfile(
+  gameName: string,
+  profileName: string,
+  modName?: string,
+  sourceName?: string,
+): Promise<void> {
+  return await invoke_rpc(
+    "delete_launch_profile",
+    {
+      gameName,
+      profileName,
+      modName: modName ?? null,
+      sourceName: sourceName ?? null,
+    },
+    () => {},
+    "Unable to delete launch profile",
+  );
+}
+
 // TODO - just make this a generic interface for both binaries/feature jobs
 interface FeatureJobOutput {
   msg: string | null;
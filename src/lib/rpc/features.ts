@@ -1,5 +1,8 @@
 import { toastStore } from "$lib/stores/ToastStore";
+import type { ModDependency } from "./bindings/ModDependency";
+import type { ModInfo } from "./bindings/ModInfo";
 import type { ModSourceData } from "./bindings/ModSourceData";
+import type { ModVersionSettings } from "./bindings/ModVersionSettings";
 import { errorLog } from "./logging";
 import { invoke_rpc } from "./rpc";
 import { unwrapFunctionStore, format } from "svelte-i18n";
@@ -148,15 +151,22 @@ export async function getModSources(): Promise<ModSource[]> {
   );
 }
 
+export interface ImportedMod {
+  modName: string;
+  version: string;
+  manifest: ModManifest | null;
+}
+
 export async function extractNewMod(
   gameName: string,
   bundlePath: string,
   modSource: string,
-): Promise<InstallationOutput> {
+): Promise<ImportedMod | undefined> {
   return await invoke_rpc(
     "extract_new_mod",
     { gameName, bundlePath, modSource },
-    () => failed("Failed to extract mod"),
+    () => undefined,
+    "Failed to extract mod",
   );
 }
 
@@ -199,10 +209,11 @@ export async function decompileForModInstall(
   gameName: string,
   modName: string | undefined,
   sourceName: string | undefined,
+  versionName?: string,
 ): Promise<InstallationOutput> {
   return await invoke_rpc(
     "decompile_for_mod_install",
-    { gameName, modName, sourceName },
+    { gameName, modName, sourceName, versionName: versionName ?? null },
     () => failed("Failed to decompile"),
   );
 }
@@ -232,6 +243,200 @@ export async function saveModInstallInfo(
   );
 }
 
+export async function rollbackModInstall(
+  gameName: string,
+  modName: string | undefined,
+  sourceName: string | undefined,
+): Promise<boolean> {
+  return await invoke_rpc(
+    "rollback_mod_install",
+    { gameName, modName, sourceName },
+    () => false,
+    "Unable to restore the previous mod version",
+  );
+}
+
+export async function getPreviousModVersions(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+): Promise<string[]> {
+  return await invoke_rpc(
+    "get_previous_mod_versions",
+    { gameName, modName, sourceName },
+    () => [],
+  );
+}
+
+export async function switchModVersion(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+  versionName: string,
+): Promise<InstallationOutput> {
+  return await invoke_rpc(
+    "switch_mod_version",
+    { gameName, modName, sourceName, versionName },
+    () => failed("Failed to switch mod version"),
+  );
+}
+
+export interface ModUpdateStatus {
+  sourceName: string;
+  modName: string;
+  installedVersion: string;
+  latestVersion: string | null;
+  updateAvailable: boolean;
+}
+
+export async function checkForModUpdates(
+  gameName: string,
+): Promise<ModUpdateStatus[]> {
+  return await invoke_rpc("check_for_mod_updates", { gameName }, () => []);
+}
+
+export async function updateMod(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+): Promise<InstallationOutput> {
+  return await invoke_rpc(
+    "update_mod",
+    { gameName, modName, sourceName },
+    () => failed("Failed to update mod"),
+  );
+}
+
+export interface PlannedModInstall {
+  sourceName: string;
+  modName: string;
+  version: string;
+  downloadUrl: string;
+  dependencies: ModDependency[];
+}
+
+export async function resolveModInstallPlan(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+  version?: string,
+): Promise<PlannedModInstall[]> {
+  return await invoke_rpc(
+    "resolve_mod_install_plan",
+    { gameName, modName, sourceName, version: version ?? null },
+    () => [],
+    "Unable to resolve mod dependencies",
+  );
+}
+
+export async function installModWithDependencies(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+  version?: string,
+  pathToIso?: string,
+): Promise<InstallationOutput> {
+  return await invoke_rpc(
+    "install_mod_with_dependencies",
+    {
+      gameName,
+      modName,
+      sourceName,
+      version: version ?? null,
+      pathToIso: pathToIso ?? null,
+    },
+    () => failed("Failed to install mod"),
+  );
+}
+
+export interface ModPackageOptions {
+  version: string;
+  displayName: string;
+  description?: string;
+  authors?: string[];
+  tags?: string[];
+  supportedGames?: string[];
+  platforms?: string[];
+  dependencies?: ModDependency[];
+  settings?: ModVersionSettings;
+  websiteUrl?: string;
+  assetBaseUrl?: string;
+  outputDir?: string;
+}
+
+export interface ModManifest {
+  id: string;
+  displayName: string;
+  version: string;
+  description: string;
+  authors: string[];
+  tags: string[];
+  supportedGames: string[];
+  cover: string | null;
+  thumbnail: string | null;
+}
+
+export interface ModPackage {
+  outputDir: string;
+  manifest: ModManifest;
+  archives: {
+    platform: string;
+    path: string;
+    fileName: string;
+    size: number;
+    sha256: string;
+  }[];
+  modInfo: ModInfo;
+  sourceEntry: string;
+}
+
+export interface LocalModInfo {
+  modName: string;
+  displayName: string;
+  installedVersion: string | null;
+  manifest: ModManifest | null;
+  coverPath: string | null;
+  thumbnailPath: string | null;
+}
+
+export async function listLocalMods(gameName: string): Promise<LocalModInfo[]> {
+  return await invoke_rpc("list_local_mods", { gameName }, () => []);
+}
+
+export async function packageLocalMod(
+  gameName: string,
+  modName: string,
+  options: ModPackageOptions,
+): Promise<ModPackage | undefined> {
+  return await invoke_rpc(
+    "package_local_mod",
+    { gameName, modName, options },
+    () => undefined,
+    "Unable to package mod",
+  );
+}
+
+export interface GeneratedModSource {
+  outputPath: string;
+  modCount: number;
+  versionCount: number;
+  warnings: string[];
+}
+
+export async function generateModSource(
+  sourceDir: string,
+  sourceName: string,
+  baseUrl: string,
+  outputPath?: string,
+): Promise<GeneratedModSource | undefined> {
+  return await invoke_rpc(
+    "generate_mod_source",
+    { sourceDir, sourceName, baseUrl, outputPath: outputPath ?? null },
+    () => undefined,
+    "Unable to generate mod source",
+  );
+}
+
 export async function getInstalledMods(
   gameName: string,
 ): Promise<Record<string, Record<string, string>>> {
@@ -245,15 +450,37 @@ export async function getInstalledMods(
   );
 }
 
+export async function getModsSecondsPlayed(
+  gameName: string,
+): Promise<Record<string, Record<string, number>>> {
+  return await invoke_rpc(
+    "get_setting_value",
+    { key: "mods_seconds_played", gameName },
+    () => {
+      let ret: Record<string, Record<string, number>> = {};
+      return ret;
+    },
+  );
+}
+
 export async function launchMod(
   gameName: string,
   inDebug: boolean,
   modName: string,
   sourceName: string,
+  launchProfile?: string,
+  allowConcurrent?: boolean,
 ): Promise<void> {
   return await invoke_rpc(
     "launch_mod",
-    { gameName, inDebug, modName, sourceName },
+    {
+      gameName,
+      inDebug,
+      modName,
+      sourceName,
+      launchProfile: launchProfile ?? null,
+      allowConcurrent: allowConcurrent ?? false,
+    },
     () => {},
     "_mirror_",
   );
@@ -313,10 +540,11 @@ export async function getLaunchModString(
   gameName: string,
   modName: string,
   sourceName: string,
+  launchProfile?: string,
 ): Promise<string> {
   return await invoke_rpc(
     "get_launch_mod_string",
-    { gameName, modName, sourceName },
+    { gameName, modName, sourceName, launchProfile: launchProfile ?? null },
     () => "_mirror_",
   );
 }
@@ -1,5 +1,68 @@
 import { invoke_rpc } from "./rpc";
 
+export interface RunningGame {
+  pid: number;
+  gameName: string;
+  modName: string | null;
+  sourceName: string | null;
+  version: string | null;
+  startTime: string;
+  logFile: string | null;
+}
+
+export interface PlaySession {
+  gameName: string;
+  modName: string | null;
+  sourceName: string | null;
+  version: string | null;
+  startTime: string;
+  endTime: string;
+  durationSeconds: number;
+  exitCode: number | null;
+  crashed: boolean;
+}
+
+export interface PlaySessionQuery {
+  gameName?: string;
+  modName?: string;
+  sourceName?: string;
+  from?: string;
+  to?: string;
+}
+
+export interface PlaySessionSummary {
+  sessionCount: number;
+  crashCount: number;
+  totalSeconds: number;
+  longestSession: PlaySession | null;
+  dailySeconds: Record<string, number>;
+  weeklySeconds: Record<string, number>;
+}
+
+export async function listRunningGames(): Promise<RunningGame[]> {
+  return await invoke_rpc("list_running_games", {}, () => []);
+}
+
+export async function isGameRunning(
+  gameName: string,
+  modName?: string,
+  sourceName?: string,
+): Promise<boolean> {
+  return await invoke_rpc(
+    "is_game_running",
+    { gameName, modName: modName ?? null, sourceName: sourceName ?? null },
+    () => false,
+  );
+}
+
+export async function stopGame(pid: number): Promise<void> {
+  return await invoke_rpc("stop_game", { pid }, () => {}, "_mirror_");
+}
+
+export async function killGame(pid: number): Promise<void> {
+  return await invoke_rpc("kill_game", { pid }, () => {}, "_mirror_");
+}
+
 export async function uninstallGame(gameName: string): Promise<boolean> {
   return await invoke_rpc(
     "uninstall_game",
@@ -28,3 +91,35 @@ export async function getFurthestGameMilestone(
     "Unable to get furthest game milestone",
   );
 }
+
+export interface CrashReport {
+  id: string;
+  gameName: string;
+  modName: string | null;
+  sourceName: string | null;
+  version: string | null;
+  launcherVersion: string;
+  exitCode: number | null;
+  startTime: string;
+  crashTime: string;
+}
+
+export async function getCrashReports(): Promise<CrashReport[]> {
+  return await invoke_rpc("get_crash_reports", {}, () => []);
+}
+
+export async function getPlaySessions(
+  query: PlaySessionQuery,
+): Promise<PlaySession[]> {
+  return await invoke_rpc("get_play_sessions", { query }, () => []);
+}
+
+export async function getPlaySessionSummary(
+  query: PlaySessionQuery,
+): Promise<PlaySessionSummary | undefined> {
+  return await invoke_rpc(
+    "get_play_session_summary",
+    { query },
+    () => undefined,
+  );
+}
@@ -0,0 +1,61 @@
+import { invoke_rpc } from "./rpc";
+
+export interface IsoLibraryImage {
+  id: string;
+  game: string;
+  region: string | null;
+  serial: string | null;
+  hash: string;
+  sourcePath: string;
+  sourceSize: number;
+  sourceModified: number;
+  registeredDate: string;
+  references: string[];
+  extracted: boolean;
+  sourceExists: boolean;
+}
+
+interface InstallationOutput {
+  msg: string | null;
+  success: boolean;
+}
+
+export async function listIsoLibrary(
+  gameName?: string,
+): Promise<IsoLibraryImage[]> {
+  return await invoke_rpc(
+    "list_iso_library",
+    { gameName: gameName ?? null },
+    () => [],
+  );
+}
+
+export async function linkKnownIso(
+  gameName: string,
+  isoId: string,
+  modName?: string,
+  sourceName?: string,
+): Promise<InstallationOutput> {
+  return await invoke_rpc(
+    "link_known_iso",
+    {
+      gameName,
+      isoId,
+      modName: modName ?? null,
+      sourceName: sourceName ?? null,
+    },
+    () => {
+      return { success: false, msg: "Failed to link ISO" };
+    },
+  );
+}
+
+export async function removeIsoFromLibrary(isoId: string): Promise<boolean> {
+  return await invoke_rpc(
+    "remove_iso_from_library",
+    { isoId },
+    () => false,
+    "_mirror_",
+    () => true,
+  );
+}
@@ -0,0 +1,80 @@
+import { invoke_rpc } from "./rpc";
+
+export interface ReplSession {
+  id: number;
+  gameName: string;
+  modName: string | null;
+  sourceName: string | null;
+  address: string;
+  pid: number | null;
+}
+
+// Payload of the `repl_output` event
+export interface ReplOutput {
+  sessionId: number;
+  source: "goalc" | "repl";
+  line: string;
+}
+
+export async function startREPL(
+  gameName: string,
+  port?: number,
+): Promise<ReplSession | undefined> {
+  return await invoke_rpc(
+    "start_repl",
+    { gameName, port: port ?? null },
+    () => undefined,
+    "Unable to start REPL",
+  );
+}
+
+export async function startREPLForMod(
+  gameName: string,
+  modName: string,
+  sourceName: string,
+  port?: number,
+): Promise<ReplSession | undefined> {
+  return await invoke_rpc(
+    "start_repl_for_mod",
+    { gameName, modName, sourceName, port: port ?? null },
+    () => undefined,
+    "Unable to start REPL",
+  );
+}
+
+export async function connectREPL(
+  gameName: string,
+  address: string,
+): Promise<ReplSession | undefined> {
+  return await invoke_rpc(
+    "connect_repl",
+    { gameName, address },
+    () => undefined,
+    "Unable to connect to REPL",
+  );
+}
+
+export async function listREPLSessions(): Promise<ReplSession[]> {
+  return await invoke_rpc("list_repl_sessions", {}, () => []);
+}
+
+export async function sendREPLForm(
+  sessionId: number,
+  form: string,
+): Promise<void> {
+  return await invoke_rpc(
+    "send_repl_form",
+    { sessionId, form },
+    () => {},
+    "Unable to send form to REPL",
+  );
+}
+
+export async function closeREPL(sessionId: number): Promise<void> {
+  return await invoke_rpc(
+    "close_repl",
+    { sessionId },
+    () => {},
+    "Unable to close REPL",
+  );
+}
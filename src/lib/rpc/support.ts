@@ -1,7 +1,9 @@
 import { saveFilePrompt } from "$lib/utils/file-dialogs";
 import { invoke_rpc } from "./rpc";
 
-export async function generateSupportPackage(): Promise<void> {
+export async function generateSupportPackage(
+  crashReportId?: string,
+): Promise<void> {
   const userPath = await saveFilePrompt(
     "ZIP",
     ["zip"],
@@ -9,7 +11,7 @@ export async function generateSupportPackage(): Promise<void> {
   );
   return await invoke_rpc(
     "generate_support_package",
-    { userPath },
+    { userPath, crashReportId: crashReportId ?? null },
     () => {},
     "Unable to create support package",
   );
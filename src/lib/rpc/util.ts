@@ -12,3 +12,17 @@ export async function pathExists(directory: string): Promise<boolean> {
 export async function isMacOSVersion15OrAbove(): Promise<boolean | undefined> {
   return await invoke_rpc("is_macos_version_15_or_above", {}, () => undefined);
 }
+
+export interface Terminal {
+  name: string;
+  path: string;
+  execArgs: string[];
+}
+
+export async function listAvailableTerminals(): Promise<Terminal[]> {
+  return await invoke_rpc("list_available_terminals", {}, () => []);
+}
+
+export async function getResolvedTerminal(): Promise<Terminal | null> {
+  return await invoke_rpc("get_resolved_terminal", {}, () => null);
+}
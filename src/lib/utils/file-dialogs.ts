@@ -60,7 +60,11 @@ export async function isoPrompt(
   fileExplanation: string,
   title: string,
 ): Promise<string | undefined> {
-  const path = await filePrompt(["ISO", "iso"], fileExplanation, title);
+  const path = await filePrompt(
+    ["ISO", "iso", "CUE", "cue", "BIN", "bin"],
+    fileExplanation,
+    title,
+  );
   if (path === null) {
     return undefined;
   }